@@ -10,18 +10,51 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 #[wasm_bindgen]
 pub struct EbnfParserParser {
     grammar: base::Grammar,
+    /// The grammar rewritten without left recursion, if it had any.
+    eliminated: Option<base::Eliminated>,
+    warnings: Array,
 }
 
 #[wasm_bindgen]
 impl EbnfParserParser {
     #[wasm_bindgen(constructor)]
     pub fn new(input: &str) -> Result<EbnfParserParser, JsValue> {
-        match ebnf::parse(input) {
-            Ok(parser_parser) => Ok(EbnfParserParser {
-                grammar: parser_parser,
-            }),
-            Err(e) => Err(Error::from(e).into()),
-        }
+        let (grammar, warnings, eliminated) = match ebnf::parse(input) {
+            Ok((grammar, warnings)) => (grammar, warnings, None),
+            Err(e) => match parse_left_recursive(input) {
+                Some((grammar, warnings, eliminated)) => (grammar, warnings, Some(eliminated)),
+                None => return Err(Error::Ebnf(e, input.to_owned()).into()),
+            },
+        };
+        Ok(EbnfParserParser {
+            grammar,
+            eliminated,
+            warnings: warnings
+                .into_iter()
+                .map(|warning| ebnf_warning(warning, input))
+                .collect(),
+        })
+    }
+
+    /// Loads a grammar precompiled with the `compile` command of the CLI.
+    #[wasm_bindgen(js_name = fromBinary)]
+    pub fn from_binary(bytes: &[u8]) -> Result<EbnfParserParser, JsValue> {
+        let grammar = match base::binary::decode(bytes) {
+            Ok(grammar) => grammar,
+            Err(e) => return Err(Error::from(e).into()),
+        };
+        // a precompiled grammar only has left-recursive rules if they were
+        // allowed when it was compiled, in which case the transform is needed
+        let eliminated = match base::eliminate_left_recursion(&grammar) {
+            Ok(eliminated) if eliminated.grammar == grammar => None,
+            Ok(eliminated) => Some(eliminated),
+            Err(e) => return Err(Error::from(e).into()),
+        };
+        Ok(EbnfParserParser {
+            grammar,
+            eliminated,
+            warnings: Array::new(),
+        })
     }
 
     #[wasm_bindgen(getter = productionRules)]
@@ -31,26 +64,89 @@ impl EbnfParserParser {
             .collect()
     }
 
-    pub fn check(&self, input: &str, initial_rule: &str) -> Option<Object> {
-        base::check(input, &self.grammar, initial_rule).map(tree)
+    /// The rule to check input from by default.
+    #[wasm_bindgen(getter = startRule)]
+    pub fn get_start_rule(&self) -> Option<String> {
+        self.grammar.start().map(str::to_owned)
+    }
+
+    /// Warnings about the rules of the grammar that are likely mistakes.
+    #[wasm_bindgen(getter)]
+    pub fn warnings(&self) -> Array {
+        self.warnings.clone()
+    }
+
+    pub fn check(&self, input: &str, initial_rule: &str) -> Result<Object, JsValue> {
+        let result = match &self.eliminated {
+            Some(eliminated) => base::check(input, &eliminated.grammar, initial_rule)
+                .map(|node| eliminated.rebuild(node)),
+            None => base::check(input, &self.grammar, initial_rule),
+        };
+        match result {
+            Ok(node) => Ok(tree(input, node)),
+            Err(e) => Err(Error::from(e).into()),
+        }
     }
 }
 
+/// Parses a grammar whose only errors are left-recursive rules, rewriting
+/// them so that it can be checked anyway.
+fn parse_left_recursive(
+    input: &str,
+) -> Option<(base::Grammar, Vec<ebnf::warning::Warning>, base::Eliminated)> {
+    let options = ebnf::Options {
+        allow_left_recursion: true,
+        ..ebnf::Options::default()
+    };
+    let (grammar, warnings) = ebnf::parse_with(input, &options).ok()?;
+    let eliminated = base::eliminate_left_recursion(&grammar).ok()?;
+    Some((grammar, warnings, eliminated))
+}
+
+/// Names of the well-formed rules of a grammar that may fail to parse.
+#[wasm_bindgen(js_name = ruleNames)]
+pub fn rule_names(input: &str) -> Array {
+    ebnf::rule_names(input).into_iter().map(JsValue::from).collect()
+}
+
 #[derive(Debug)]
 pub enum Error {
-    Ebnf(ebnf::error::Error),
+    /// Errors in a grammar, along with its source.
+    Ebnf(Vec<ebnf::error::Error>, String),
+    Check(base::CheckError),
+    Decode(base::binary::DecodeError),
+    /// Left recursion in a precompiled grammar that can't be rewritten.
+    Elimination(base::EliminationError),
 }
 
-impl From<ebnf::error::Error> for Error {
-    fn from(error: ebnf::error::Error) -> Error {
-        Error::Ebnf(error)
+impl From<base::CheckError> for Error {
+    fn from(error: base::CheckError) -> Error {
+        Error::Check(error)
+    }
+}
+
+impl From<base::binary::DecodeError> for Error {
+    fn from(error: base::binary::DecodeError) -> Error {
+        Error::Decode(error)
+    }
+}
+
+impl From<base::EliminationError> for Error {
+    fn from(error: base::EliminationError) -> Error {
+        Error::Elimination(error)
     }
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::Ebnf(inner) => write!(f, "{}", inner),
+            Error::Ebnf(inner, _) => {
+                let messages = inner.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "{}", messages.join("\n"))
+            }
+            Error::Check(inner) => write!(f, "{}", inner),
+            Error::Decode(inner) => write!(f, "{}", inner),
+            Error::Elimination(inner) => write!(f, "{}", inner),
         }
     }
 }
@@ -58,7 +154,10 @@ impl fmt::Display for Error {
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Error::Ebnf(inner) => Some(inner),
+            Error::Ebnf(inner, _) => inner.first().map(|error| error as _),
+            Error::Check(inner) => Some(inner),
+            Error::Decode(inner) => Some(inner),
+            Error::Elimination(inner) => Some(inner),
         }
     }
 }
@@ -67,28 +166,41 @@ impl std::error::Error for Error {
 impl Into<JsValue> for Error {
     fn into(self) -> JsValue {
         match self {
-            Error::Ebnf(inner) => {
-                let from = Object::new();
+            Error::Ebnf(inner, source) => inner
+                .into_iter()
+                .map(|error| ebnf_error(error, &source))
+                .collect::<Array>()
+                .into(),
+            Error::Check(inner) => {
+                let expected: Array = inner
+                    .expected
+                    .iter()
+                    .map(|expected| JsValue::from(expected.to_string()))
+                    .collect();
+                let stack: Array = inner.stack.iter().map(JsValue::from).collect();
+                let error = Object::new();
                 unsafe {
-                    Reflect::set(&from, &"line".into(), &(inner.span.from.line as u32).into())
-                        .unwrap();
-                    Reflect::set(&from, &"ch".into(), &(inner.span.from.column as u32).into())
+                    Reflect::set(&error, &"kind".into(), &inner.to_string().into()).unwrap();
+                    Reflect::set(&error, &"position".into(), &(inner.position as u32).into())
                         .unwrap();
+                    Reflect::set(&error, &"expected".into(), &expected.into()).unwrap();
+                    Reflect::set(&error, &"stack".into(), &stack.into()).unwrap();
                 }
-                let to = Object::new();
-                unsafe {
-                    Reflect::set(&to, &"line".into(), &(inner.span.to.line as u32).into()).unwrap();
-                    Reflect::set(&to, &"ch".into(), &(inner.span.to.column as u32).into()).unwrap();
-                }
-                let span = Object::new();
+                error.into()
+            }
+            Error::Decode(inner) => {
+                let error = Object::new();
                 unsafe {
-                    Reflect::set(&span, &"from".into(), &from.into()).unwrap();
-                    Reflect::set(&span, &"to".into(), &to.into()).unwrap();
+                    Reflect::set(&error, &"kind".into(), &inner.to_string().into()).unwrap();
                 }
+                error.into()
+            }
+            Error::Elimination(inner) => {
+                let rules: Array = inner.rules.iter().map(JsValue::from).collect();
                 let error = Object::new();
                 unsafe {
                     Reflect::set(&error, &"kind".into(), &inner.to_string().into()).unwrap();
-                    Reflect::set(&error, &"span".into(), &span.into()).unwrap();
+                    Reflect::set(&error, &"rules".into(), &rules.into()).unwrap();
                 }
                 error.into()
             }
@@ -97,25 +209,116 @@ impl Into<JsValue> for Error {
 }
 
 #[allow(unused_unsafe)]
-fn tree(node: base::Node) -> Object {
+fn ebnf_location(location: ebnf::span::Location) -> Object {
+    let obj = Object::new();
+    unsafe {
+        Reflect::set(&obj, &"line".into(), &(location.line as u32).into()).unwrap();
+        // CodeMirror counts columns in UTF-16 code units
+        Reflect::set(&obj, &"ch".into(), &(location.utf16_column as u32).into()).unwrap();
+        Reflect::set(&obj, &"offset".into(), &(location.offset as u32).into()).unwrap();
+        Reflect::set(&obj, &"column".into(), &(location.column as u32).into()).unwrap();
+    }
+    obj
+}
+
+#[allow(unused_unsafe)]
+fn ebnf_span(span: ebnf::span::Span) -> Object {
+    let obj = Object::new();
+    unsafe {
+        Reflect::set(&obj, &"from".into(), &ebnf_location(span.from).into()).unwrap();
+        Reflect::set(&obj, &"to".into(), &ebnf_location(span.to).into()).unwrap();
+    }
+    obj
+}
+
+#[allow(unused_unsafe)]
+fn ebnf_error(inner: ebnf::error::Error, source: &str) -> JsValue {
+    let error = Object::new();
+    unsafe {
+        Reflect::set(&error, &"kind".into(), &inner.to_string().into()).unwrap();
+        Reflect::set(&error, &"span".into(), &ebnf_span(inner.span).into()).unwrap();
+        Reflect::set(&error, &"rendered".into(), &inner.render(source, false).into()).unwrap();
+    }
+    if let Some(fix) = inner.fix() {
+        let obj = Object::new();
+        unsafe {
+            Reflect::set(&obj, &"span".into(), &ebnf_span(fix.span).into()).unwrap();
+            Reflect::set(&obj, &"replacement".into(), &fix.replacement.into()).unwrap();
+            Reflect::set(&error, &"fix".into(), &obj.into()).unwrap();
+        }
+    }
+    error.into()
+}
+
+#[allow(unused_unsafe)]
+fn ebnf_warning(inner: ebnf::warning::Warning, source: &str) -> JsValue {
+    let warning = Object::new();
+    unsafe {
+        Reflect::set(&warning, &"kind".into(), &inner.to_string().into()).unwrap();
+        Reflect::set(&warning, &"span".into(), &ebnf_span(inner.span).into()).unwrap();
+        Reflect::set(&warning, &"rendered".into(), &inner.render(source, false).into()).unwrap();
+    }
+    warning.into()
+}
+
+/// The column of a position in UTF-16 code units, which CodeMirror counts
+/// columns in.
+fn utf16_column(input: &str, position: base::Position) -> usize {
+    input[..position.offset]
+        .chars()
+        .rev()
+        .take(position.column)
+        .map(char::len_utf16)
+        .sum()
+}
+
+#[allow(unused_unsafe)]
+fn position(input: &str, position: base::Position) -> Object {
+    let obj = Object::new();
+    let ch = utf16_column(input, position);
+    unsafe {
+        Reflect::set(&obj, &"line".into(), &(position.line as u32).into()).unwrap();
+        Reflect::set(&obj, &"ch".into(), &(ch as u32).into()).unwrap();
+        Reflect::set(&obj, &"offset".into(), &(position.offset as u32).into()).unwrap();
+        Reflect::set(&obj, &"char".into(), &(position.char as u32).into()).unwrap();
+    }
+    obj
+}
+
+#[allow(unused_unsafe)]
+fn span(input: &str, span: base::Span) -> Object {
+    let obj = Object::new();
+    unsafe {
+        Reflect::set(&obj, &"from".into(), &position(input, span.from).into()).unwrap();
+        Reflect::set(&obj, &"to".into(), &position(input, span.to).into()).unwrap();
+    }
+    obj
+}
+
+#[allow(unused_unsafe)]
+fn tree(input: &str, node: base::Node) -> Object {
     match node {
-        base::Node::Terminal(string) => {
+        base::Node::Terminal(string, node_span) => {
             let obj = Object::new();
             unsafe {
                 Reflect::set(&obj, &"name".into(), &format!("\"{}\"", string).into())
                     .unwrap();
+                Reflect::set(&obj, &"span".into(), &span(input, node_span).into())
+                    .unwrap();
             }
             return obj;
         },
-        base::Node::Nonterminal(name, nodes) => {
+        base::Node::Nonterminal(name, nodes, node_span) => {
             let obj = Object::new();
-            let children: Vec<Object> = nodes.iter().cloned().map(tree).collect();
+            let children: Vec<Object> = nodes.iter().cloned().map(|node| tree(input, node)).collect();
             let children_array: Array = children.into_iter().map(JsValue::from).collect();
             unsafe {
                 Reflect::set(&obj, &"name".into(), &name.into())
                     .unwrap();
                 Reflect::set(&obj, &"children".into(), &children_array.into())
                         .unwrap();
+                Reflect::set(&obj, &"span".into(), &span(input, node_span).into())
+                    .unwrap();
             }
             return obj;
         }
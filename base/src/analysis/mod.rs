@@ -0,0 +1,214 @@
+//! Analyses of grammars shared by the builder, the elimination of left
+//! recursion and the preprocessor of text grammars.
+
+#[cfg(test)]
+mod tests;
+
+use super::ast::{Expression, Grammar};
+use super::special::SpecialRegistry;
+use super::visit::Visitor;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Whether an expression may match empty input, given the rules that may.
+/// An exception may when its subject may: whether its restriction matches
+/// empty input too isn't always known, and taking it to be nullable errs on
+/// the side of reporting left recursion.
+struct Nullable<'r> {
+    nullable: &'r HashSet<String>,
+    specials: &'r SpecialRegistry,
+    result: bool,
+}
+
+impl<'r> Nullable<'r> {
+    fn check(&mut self, expression: &Expression) -> bool {
+        self.visit_expression(expression);
+        self.result
+    }
+}
+
+impl<'a, 'r> Visitor<'a> for Nullable<'r> {
+    fn visit_alternative(
+        &mut self,
+        first: &'a Expression,
+        second: &'a Expression,
+        rest: &'a [Expression],
+    ) {
+        self.result = [first, second]
+            .iter()
+            .copied()
+            .chain(rest.iter())
+            .any(|expression| self.check(expression));
+    }
+
+    fn visit_sequence(
+        &mut self,
+        first: &'a Expression,
+        second: &'a Expression,
+        rest: &'a [Expression],
+    ) {
+        self.result = [first, second]
+            .iter()
+            .copied()
+            .chain(rest.iter())
+            .all(|expression| self.check(expression));
+    }
+
+    fn visit_optional(&mut self, _: &'a Expression) {
+        self.result = true;
+    }
+
+    fn visit_repeated(&mut self, _: &'a Expression) {
+        self.result = true;
+    }
+
+    fn visit_factor(&mut self, count: usize, primary: &'a Expression) {
+        self.result = count == 0 || self.check(primary);
+    }
+
+    fn visit_exception(&mut self, subject: &'a Expression, _: &'a Expression) {
+        self.result = self.check(subject);
+    }
+
+    fn visit_nonterminal(&mut self, identifier: &'a str) {
+        self.result = self.nullable.contains(identifier);
+    }
+
+    fn visit_terminal(&mut self, content: &'a str) {
+        self.result = content.is_empty();
+    }
+
+    fn visit_special(&mut self, content: &'a str) {
+        self.result = self.specials.is_nullable(content);
+    }
+
+    fn visit_empty(&mut self) {
+        self.result = true;
+    }
+}
+
+pub(crate) fn is_nullable(
+    expression: &Expression,
+    nullable: &HashSet<String>,
+    specials: &SpecialRegistry,
+) -> bool {
+    Nullable {
+        nullable,
+        specials,
+        result: false,
+    }
+    .check(expression)
+}
+
+/// The rules that may match empty input.
+pub(crate) fn nullable_rules(grammar: &Grammar, specials: &SpecialRegistry) -> HashSet<String> {
+    let mut nullable = HashSet::new();
+    loop {
+        let found = grammar
+            .iter()
+            .filter(|(rule, expression)| {
+                !nullable.contains(*rule) && is_nullable(expression, &nullable, specials)
+            })
+            .map(|(rule, _)| rule.clone())
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            return nullable;
+        }
+        nullable.extend(found);
+    }
+}
+
+/// Collects the rules that may be checked before any input is consumed. The
+/// restriction of an exception is checked where its subject starts, so its
+/// rules are collected too.
+struct LeftCorners<'a, 'r> {
+    nullable: &'r HashSet<String>,
+    specials: &'r SpecialRegistry,
+    corners: Vec<&'a str>,
+}
+
+impl<'a, 'r> Visitor<'a> for LeftCorners<'a, 'r> {
+    fn visit_sequence(
+        &mut self,
+        first: &'a Expression,
+        second: &'a Expression,
+        rest: &'a [Expression],
+    ) {
+        for expression in [first, second].iter().copied().chain(rest.iter()) {
+            self.visit_expression(expression);
+            if !is_nullable(expression, self.nullable, self.specials) {
+                break;
+            }
+        }
+    }
+
+    fn visit_factor(&mut self, count: usize, primary: &'a Expression) {
+        if count > 0 {
+            self.visit_expression(primary);
+        }
+    }
+
+    fn visit_nonterminal(&mut self, identifier: &'a str) {
+        self.corners.push(identifier);
+    }
+}
+
+pub(crate) fn left_corners<'a>(
+    expression: &'a Expression,
+    nullable: &HashSet<String>,
+    specials: &SpecialRegistry,
+) -> Vec<&'a str> {
+    let mut left_corners = LeftCorners {
+        nullable,
+        specials,
+        corners: Vec::new(),
+    };
+    left_corners.visit_expression(expression);
+    left_corners.corners
+}
+
+/// The shortest chain of rules through which `rule` checks itself again
+/// before consuming input, if there is one.
+fn left_recursion_chain(
+    grammar: &Grammar,
+    nullable: &HashSet<String>,
+    specials: &SpecialRegistry,
+    rule: &str,
+) -> Option<Vec<String>> {
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut pending = VecDeque::from(vec![rule]);
+    while let Some(current) = pending.pop_front() {
+        let corners = match grammar.get(current) {
+            Some(expression) => left_corners(expression, nullable, specials),
+            None => Vec::new(),
+        };
+        for corner in corners {
+            if corner == rule {
+                let mut chain = vec![rule.to_owned(), current.to_owned()];
+                let mut link = current;
+                while link != rule {
+                    link = previous[link];
+                    chain.push(link.to_owned());
+                }
+                chain.reverse();
+                return Some(chain);
+            }
+            if !previous.contains_key(corner) {
+                previous.insert(corner, current);
+                pending.push_back(corner);
+            }
+        }
+    }
+    None
+}
+
+/// Finds the rules that may check themselves again before consuming input,
+/// which the backtracking engine can't check. Each is given as the shortest
+/// chain of rules it goes through, from and back to itself, in the order the
+/// rules were declared in.
+pub fn left_recursion(grammar: &Grammar, specials: &SpecialRegistry) -> Vec<Vec<String>> {
+    let nullable = nullable_rules(grammar, specials);
+    grammar
+        .keys()
+        .filter_map(|rule| left_recursion_chain(grammar, &nullable, specials, rule))
+        .collect()
+}
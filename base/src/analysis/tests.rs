@@ -0,0 +1,63 @@
+use super::{left_corners, nullable_rules};
+use crate::ast::Grammar;
+use crate::builder::{empty, nonterminal, special, terminal};
+use crate::special::SpecialRegistry;
+use crate::{alt, seq};
+use std::collections::HashSet;
+
+#[test]
+fn test_nullable_rules() {
+    // a = [ 'x' ] - 'y';
+    // b = 'x' - [ 'y' ];
+    // c = 0 * 'x', a, { b };
+    // d = c, ? letter ? | d;
+    // e = ? word ?;
+    let mut grammar = Grammar::new();
+    grammar.insert("a".to_owned(), terminal("x").opt().except(terminal("y")));
+    grammar.insert("b".to_owned(), terminal("x").except(terminal("y").opt()));
+    grammar.insert(
+        "c".to_owned(),
+        seq![
+            terminal("x").times(0),
+            nonterminal("a"),
+            nonterminal("b").rep()
+        ],
+    );
+    grammar.insert(
+        "d".to_owned(),
+        alt![
+            seq![nonterminal("c"), special(" letter ")],
+            nonterminal("d")
+        ],
+    );
+    grammar.insert("e".to_owned(), special("word"));
+    let nullable = |specials: &SpecialRegistry| {
+        let mut rules = nullable_rules(&grammar, specials)
+            .into_iter()
+            .collect::<Vec<_>>();
+        rules.sort();
+        rules
+    };
+    assert_eq!(nullable(&SpecialRegistry::new()), vec!["a", "c", "e"]);
+    let mut specials = SpecialRegistry::new();
+    specials.insert_consuming("word", |_| None);
+    assert_eq!(nullable(&specials), vec!["a", "c"]);
+}
+
+#[test]
+fn test_left_corners() {
+    // a, 'x' | [ b ], c, d | 0 * e | f - g | { 'y' }, h, i | ;
+    let expression = alt![
+        seq![nonterminal("a"), terminal("x")],
+        seq![nonterminal("b").opt(), nonterminal("c"), nonterminal("d")],
+        nonterminal("e").times(0),
+        nonterminal("f").except(nonterminal("g")),
+        seq![terminal("y").rep(), nonterminal("h"), nonterminal("i")],
+        empty()
+    ];
+    let nullable = ["h".to_owned()].iter().cloned().collect::<HashSet<_>>();
+    assert_eq!(
+        left_corners(&expression, &nullable, &SpecialRegistry::new()),
+        vec!["a", "b", "c", "f", "g", "h", "i"]
+    );
+}
@@ -1,8 +1,11 @@
+use super::span::Span;
 use std::collections::HashMap;
-
-pub type Grammar = HashMap<String, Expression>;
+use std::iter::FromIterator;
+use std::ops::Index;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Expression {
     Alternative {
         first: Box<Expression>,
@@ -29,3 +32,173 @@ pub enum Expression {
     Special(String),
     Empty,
 }
+
+/// A rule of a grammar, along with what is known about where it comes from.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rule {
+    pub name: String,
+    pub expression: Expression,
+    /// The comments right above the rule, without their delimiters.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub doc: Option<String>,
+    /// Where the rule is defined, if it was read from source.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub span: Option<Span>,
+}
+
+impl Rule {
+    pub fn new(name: &str, expression: Expression) -> Rule {
+        Rule {
+            name: name.to_owned(),
+            expression,
+            doc: None,
+            span: None,
+        }
+    }
+}
+
+/// Rules in the order they were declared in, along with the rule to check
+/// input from.
+#[derive(Debug, Clone, Default)]
+pub struct Grammar {
+    rules: Vec<Rule>,
+    /// Position of each rule in `rules`.
+    indices: HashMap<String, usize>,
+    start: Option<String>,
+}
+
+impl Grammar {
+    pub fn new() -> Grammar {
+        Grammar {
+            rules: Vec::new(),
+            indices: HashMap::new(),
+            start: None,
+        }
+    }
+
+    /// Adds a rule after the others, or replaces the one with the same name
+    /// in its place. Returns the replaced rule.
+    pub fn insert_rule(&mut self, rule: Rule) -> Option<Rule> {
+        match self.indices.get(&rule.name) {
+            Some(&index) => Some(std::mem::replace(&mut self.rules[index], rule)),
+            None => {
+                self.indices.insert(rule.name.clone(), self.rules.len());
+                self.rules.push(rule);
+                None
+            }
+        }
+    }
+
+    /// Adds a rule after the others, or replaces the expression of the one
+    /// with the same name, keeping its place, doc comment and span. Returns
+    /// the replaced expression.
+    pub fn insert(&mut self, name: String, expression: Expression) -> Option<Expression> {
+        match self.indices.get(&name) {
+            Some(&index) => Some(std::mem::replace(
+                &mut self.rules[index].expression,
+                expression,
+            )),
+            None => {
+                self.insert_rule(Rule::new(&name, expression));
+                None
+            }
+        }
+    }
+
+    pub fn rule(&self, name: &str) -> Option<&Rule> {
+        self.indices.get(name).map(|&index| &self.rules[index])
+    }
+
+    pub fn rule_mut(&mut self, name: &str) -> Option<&mut Rule> {
+        let index = *self.indices.get(name)?;
+        Some(&mut self.rules[index])
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Expression> {
+        self.rule(name).map(|rule| &rule.expression)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.indices.contains_key(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The rules in the order they were declared in.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// The names of the rules in the order they were declared in.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.rules.iter().map(|rule| &rule.name)
+    }
+
+    /// The names and expressions of the rules in the order they were
+    /// declared in.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Expression)> {
+        self.rules.iter().map(|rule| (&rule.name, &rule.expression))
+    }
+
+    /// The rule to check input from, which is the first one unless set
+    /// otherwise.
+    pub fn start(&self) -> Option<&str> {
+        self.start
+            .as_deref()
+            .or_else(|| self.rules.first().map(|rule| rule.name.as_str()))
+    }
+
+    pub fn set_start(&mut self, name: &str) {
+        self.start = Some(name.to_owned());
+    }
+}
+
+/// Grammars are equal when they declare the same rules in the same order and
+/// start from the same one. Doc comments and spans are left out, as they
+/// don't change what a grammar matches.
+impl PartialEq for Grammar {
+    fn eq(&self, other: &Grammar) -> bool {
+        self.start() == other.start()
+            && self.rules.len() == other.rules.len()
+            && self
+                .iter()
+                .zip(other.iter())
+                .all(|(rule, other)| rule == other)
+    }
+}
+
+impl Eq for Grammar {}
+
+impl Index<&str> for Grammar {
+    type Output = Expression;
+
+    fn index(&self, name: &str) -> &Expression {
+        self.get(name).expect("no rule with this name")
+    }
+}
+
+impl FromIterator<(String, Expression)> for Grammar {
+    fn from_iter<I>(iter: I) -> Grammar
+    where
+        I: IntoIterator<Item = (String, Expression)>,
+    {
+        let mut grammar = Grammar::new();
+        for (name, expression) in iter {
+            grammar.insert(name, expression);
+        }
+        grammar
+    }
+}
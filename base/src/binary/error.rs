@@ -0,0 +1,38 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DecodeError {
+    /// The input doesn't start with the magic bytes of a precompiled grammar.
+    NotAGrammar,
+    /// The version of the format the grammar was written in.
+    UnsupportedVersion(u16),
+    /// The checksum doesn't match the contents.
+    Corrupted,
+    Truncated,
+    /// Contents that pass the checksum but don't make up a grammar, which
+    /// only an encoder other than [`super::encode`] could have written.
+    Malformed(&'static str),
+    UndefinedRule(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::NotAGrammar => write!(f, "not a precompiled grammar"),
+            DecodeError::UnsupportedVersion(version) => write!(
+                f,
+                "precompiled grammar version {} is not supported, expected version {}",
+                version,
+                super::VERSION
+            ),
+            DecodeError::Corrupted => write!(f, "precompiled grammar is corrupted"),
+            DecodeError::Truncated => write!(f, "precompiled grammar ends unexpectedly"),
+            DecodeError::Malformed(reason) => {
+                write!(f, "precompiled grammar is malformed: {}", reason)
+            }
+            DecodeError::UndefinedRule(rule) => write!(f, "rule {} is undefined", rule),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
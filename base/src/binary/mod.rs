@@ -0,0 +1,424 @@
+//! A compact binary format for grammars that have already been validated,
+//! so that they can be loaded without being parsed again.
+//!
+//! All integers are unsigned LEB128 unless stated otherwise. A grammar is
+//! written as:
+//!
+//! - the magic bytes [`MAGIC`],
+//! - the format [`VERSION`], as a little-endian `u16`,
+//! - the FNV-1a hash of everything that follows, as a little-endian `u32`,
+//! - the string table: the number of strings, then the length and UTF-8
+//!   bytes of each, every rule name, doc comment and expression content
+//!   stored once,
+//! - the expression arena: the number of expressions, then each of them as
+//!   a tag byte followed by its operands, which are indices into the string
+//!   table or of expressions earlier in the arena,
+//! - the rule table: the number of rules, then the name, expression, a byte
+//!   of flags and, if the flags say so, the doc comment and span of each
+//!   rule, in the order they were declared in,
+//! - the start rule, as zero for none or one more than its index in the
+//!   rule table.
+//!
+//! The version is bumped on any change to the layout, and grammars written
+//! in other versions are rejected rather than misread.
+
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+use super::ast::{Expression, Grammar, Rule};
+use super::span::{Position, Span};
+use super::visit::Visitor;
+pub use error::DecodeError;
+use std::collections::HashMap;
+
+pub const MAGIC: [u8; 4] = *b"\0ppg";
+pub const VERSION: u16 = 1;
+
+const ALTERNATIVE: u8 = 0;
+const SEQUENCE: u8 = 1;
+const OPTIONAL: u8 = 2;
+const REPEATED: u8 = 3;
+const FACTOR: u8 = 4;
+const EXCEPTION: u8 = 5;
+const NONTERMINAL: u8 = 6;
+const TERMINAL: u8 = 7;
+const SPECIAL: u8 = 8;
+const EMPTY: u8 = 9;
+
+const HAS_DOC: u8 = 1;
+const HAS_SPAN: u8 = 2;
+
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+fn write_integer(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+#[derive(Default)]
+struct Encoder<'a> {
+    strings: Vec<&'a str>,
+    /// Index of each string in `strings`.
+    interned: HashMap<&'a str, usize>,
+    arena: Vec<u8>,
+    expressions: usize,
+}
+
+impl<'a> Encoder<'a> {
+    fn string(&mut self, string: &'a str) -> usize {
+        let strings = &mut self.strings;
+        *self.interned.entry(string).or_insert_with(|| {
+            strings.push(string);
+            strings.len() - 1
+        })
+    }
+
+    /// Writes an expression after its operands, and returns its index.
+    fn expression(&mut self, expression: &'a Expression) -> usize {
+        let operands = match expression {
+            Expression::Alternative {
+                first: box first,
+                second: box second,
+                rest,
+            }
+            | Expression::Sequence {
+                first: box first,
+                second: box second,
+                rest,
+            } => {
+                let mut operands = vec![2 + rest.len()];
+                for expression in [first, second].iter().copied().chain(rest.iter()) {
+                    operands.push(self.expression(expression));
+                }
+                operands
+            }
+            Expression::Optional(box inner) => vec![self.expression(inner)],
+            Expression::Repeated(box inner) => vec![self.expression(inner)],
+            Expression::Factor {
+                count,
+                primary: box primary,
+            } => vec![*count, self.expression(primary)],
+            Expression::Exception {
+                subject: box subject,
+                restriction: box restriction,
+            } => vec![self.expression(subject), self.expression(restriction)],
+            Expression::Nonterminal(identifier) => vec![self.string(identifier)],
+            Expression::Terminal(content) => vec![self.string(content)],
+            Expression::Special(content) => vec![self.string(content)],
+            Expression::Empty => vec![],
+        };
+        let tag = match expression {
+            Expression::Alternative { .. } => ALTERNATIVE,
+            Expression::Sequence { .. } => SEQUENCE,
+            Expression::Optional(_) => OPTIONAL,
+            Expression::Repeated(_) => REPEATED,
+            Expression::Factor { .. } => FACTOR,
+            Expression::Exception { .. } => EXCEPTION,
+            Expression::Nonterminal(_) => NONTERMINAL,
+            Expression::Terminal(_) => TERMINAL,
+            Expression::Special(_) => SPECIAL,
+            Expression::Empty => EMPTY,
+        };
+        self.arena.push(tag);
+        for operand in operands {
+            write_integer(&mut self.arena, operand);
+        }
+        self.expressions += 1;
+        self.expressions - 1
+    }
+}
+
+fn write_position(bytes: &mut Vec<u8>, position: Position) {
+    for value in [
+        position.offset,
+        position.char,
+        position.line,
+        position.column,
+    ]
+    .iter()
+    {
+        write_integer(bytes, *value);
+    }
+}
+
+/// Writes a grammar in the binary format. The grammar is expected to be
+/// valid, as it won't be validated again when it's read back.
+pub fn encode(grammar: &Grammar) -> Vec<u8> {
+    let mut encoder = Encoder::default();
+    let mut rules = Vec::new();
+    write_integer(&mut rules, grammar.len());
+    for rule in grammar.rules() {
+        write_integer(&mut rules, encoder.string(&rule.name));
+        write_integer(&mut rules, encoder.expression(&rule.expression));
+        let mut flags = 0;
+        if rule.doc.is_some() {
+            flags |= HAS_DOC;
+        }
+        if rule.span.is_some() {
+            flags |= HAS_SPAN;
+        }
+        rules.push(flags);
+        if let Some(doc) = &rule.doc {
+            write_integer(&mut rules, encoder.string(doc));
+        }
+        if let Some(span) = rule.span {
+            write_position(&mut rules, span.from);
+            write_position(&mut rules, span.to);
+        }
+    }
+    let start = grammar
+        .start()
+        .and_then(|start| grammar.keys().position(|name| name == start))
+        .map_or(0, |index| index + 1);
+    write_integer(&mut rules, start);
+
+    let mut payload = Vec::new();
+    write_integer(&mut payload, encoder.strings.len());
+    for string in encoder.strings.iter() {
+        write_integer(&mut payload, string.len());
+        payload.extend_from_slice(string.as_bytes());
+    }
+    write_integer(&mut payload, encoder.expressions);
+    payload.extend(encoder.arena);
+    payload.extend(rules);
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&checksum(&payload).to_le_bytes());
+    bytes.extend(payload);
+    bytes
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    strings: Vec<&'a str>,
+    /// Expressions not yet used as an operand of another one.
+    arena: Vec<Option<Expression>>,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < length {
+            return Err(DecodeError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn integer(&mut self) -> Result<usize, DecodeError> {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as usize;
+            if shift >= usize::BITS || (bits << shift) >> shift != bits {
+                return Err(DecodeError::Malformed("integer is too large"));
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn string(&mut self) -> Result<&'a str, DecodeError> {
+        let index = self.integer()?;
+        self.strings
+            .get(index)
+            .copied()
+            .ok_or(DecodeError::Malformed("string index is out of range"))
+    }
+
+    /// Takes an expression out of the arena, such that no expression is the
+    /// operand of two others.
+    fn operand(&mut self) -> Result<Expression, DecodeError> {
+        let index = self.integer()?;
+        self.arena
+            .get_mut(index)
+            .and_then(Option::take)
+            .ok_or(DecodeError::Malformed(
+                "expression index is out of range or reused",
+            ))
+    }
+
+    fn expression(&mut self) -> Result<Expression, DecodeError> {
+        let expression = match self.byte()? {
+            tag @ ALTERNATIVE | tag @ SEQUENCE => {
+                let count = self.integer()?;
+                if count < 2 {
+                    return Err(DecodeError::Malformed("too few operands"));
+                }
+                let first = Box::new(self.operand()?);
+                let second = Box::new(self.operand()?);
+                let rest = (2..count)
+                    .map(|_| self.operand())
+                    .collect::<Result<Vec<_>, _>>()?;
+                match tag {
+                    ALTERNATIVE => Expression::Alternative {
+                        first,
+                        second,
+                        rest,
+                    },
+                    _ => Expression::Sequence {
+                        first,
+                        second,
+                        rest,
+                    },
+                }
+            }
+            OPTIONAL => Expression::Optional(Box::new(self.operand()?)),
+            REPEATED => Expression::Repeated(Box::new(self.operand()?)),
+            FACTOR => Expression::Factor {
+                count: self.integer()?,
+                primary: Box::new(self.operand()?),
+            },
+            EXCEPTION => Expression::Exception {
+                subject: Box::new(self.operand()?),
+                restriction: Box::new(self.operand()?),
+            },
+            NONTERMINAL => Expression::Nonterminal(self.string()?.to_owned()),
+            TERMINAL => Expression::Terminal(self.string()?.to_owned()),
+            SPECIAL => Expression::Special(self.string()?.to_owned()),
+            EMPTY => Expression::Empty,
+            _ => return Err(DecodeError::Malformed("unknown expression tag")),
+        };
+        Ok(expression)
+    }
+
+    fn position(&mut self) -> Result<Position, DecodeError> {
+        Ok(Position {
+            offset: self.integer()?,
+            char: self.integer()?,
+            line: self.integer()?,
+            column: self.integer()?,
+        })
+    }
+
+    fn rule(&mut self) -> Result<Rule, DecodeError> {
+        let name = self.string()?;
+        let expression = self.operand()?;
+        let flags = self.byte()?;
+        if flags & !(HAS_DOC | HAS_SPAN) != 0 {
+            return Err(DecodeError::Malformed("unknown rule flags"));
+        }
+        let doc = match flags & HAS_DOC {
+            0 => None,
+            _ => Some(self.string()?.to_owned()),
+        };
+        let span = match flags & HAS_SPAN {
+            0 => None,
+            _ => Some(Span {
+                from: self.position()?,
+                to: self.position()?,
+            }),
+        };
+        Ok(Rule {
+            doc,
+            span,
+            ..Rule::new(name, expression)
+        })
+    }
+}
+
+/// Finds the rules an expression uses that the grammar doesn't define.
+struct UndefinedRules<'a> {
+    grammar: &'a Grammar,
+    found: Vec<&'a str>,
+}
+
+impl<'a> Visitor<'a> for UndefinedRules<'a> {
+    fn visit_nonterminal(&mut self, identifier: &'a str) {
+        if !self.grammar.contains_key(identifier) {
+            self.found.push(identifier);
+        }
+    }
+}
+
+/// Reads a grammar written by [`encode`], checking that it was written in
+/// this version of the format and hasn't been damaged since. Only checks
+/// that the grammar is well-formed, as it was validated before it was
+/// written.
+pub fn decode(bytes: &[u8]) -> Result<Grammar, DecodeError> {
+    if !bytes.starts_with(&MAGIC) {
+        return Err(DecodeError::NotAGrammar);
+    }
+    let mut decoder = Decoder {
+        bytes: &bytes[MAGIC.len()..],
+        strings: Vec::new(),
+        arena: Vec::new(),
+    };
+    let version = decoder.take(2)?;
+    let version = u16::from_le_bytes([version[0], version[1]]);
+    if version != VERSION {
+        return Err(DecodeError::UnsupportedVersion(version));
+    }
+    let expected = decoder.take(4)?;
+    let expected = u32::from_le_bytes([expected[0], expected[1], expected[2], expected[3]]);
+    if checksum(decoder.bytes) != expected {
+        return Err(DecodeError::Corrupted);
+    }
+
+    let strings = decoder.integer()?;
+    for _ in 0..strings {
+        let length = decoder.integer()?;
+        let string = std::str::from_utf8(decoder.take(length)?)
+            .map_err(|_| DecodeError::Malformed("string is not valid UTF-8"))?;
+        decoder.strings.push(string);
+    }
+    let expressions = decoder.integer()?;
+    for _ in 0..expressions {
+        let expression = decoder.expression()?;
+        decoder.arena.push(Some(expression));
+    }
+
+    let mut grammar = Grammar::new();
+    let rules = decoder.integer()?;
+    for _ in 0..rules {
+        let rule = decoder.rule()?;
+        if grammar.insert_rule(rule).is_some() {
+            return Err(DecodeError::Malformed("rule is defined more than once"));
+        }
+    }
+    match decoder.integer()? {
+        0 => {}
+        start => {
+            let start = grammar
+                .keys()
+                .nth(start - 1)
+                .ok_or(DecodeError::Malformed("start rule index is out of range"))?
+                .clone();
+            grammar.set_start(&start);
+        }
+    }
+    if !decoder.bytes.is_empty() {
+        return Err(DecodeError::Malformed("unexpected bytes after the grammar"));
+    }
+    if decoder.arena.iter().any(Option::is_some) {
+        return Err(DecodeError::Malformed("expression is not part of any rule"));
+    }
+    let mut undefined = UndefinedRules {
+        grammar: &grammar,
+        found: Vec::new(),
+    };
+    for (_, expression) in grammar.iter() {
+        undefined.visit_expression(expression);
+    }
+    if let Some(rule) = undefined.found.first() {
+        return Err(DecodeError::UndefinedRule(rule.to_string()));
+    }
+    Ok(grammar)
+}
@@ -0,0 +1,133 @@
+use super::{checksum, decode, encode, DecodeError, MAGIC, VERSION};
+use crate::ast::{Expression, Grammar, Rule};
+use crate::builder::{empty, nonterminal, special, terminal};
+use crate::span::{Position, Span};
+use crate::{alt, seq};
+
+// list = '[', [ item, { ',', item } ], ']';
+// item = list | 3 * ?digit? - '000' | ;
+fn grammar() -> Grammar {
+    let mut grammar = Grammar::new();
+    grammar.insert_rule(Rule {
+        doc: Some("values separated by commas".to_owned()),
+        span: Some(Span {
+            from: Position::default(),
+            to: Position {
+                offset: 42,
+                char: 41,
+                line: 0,
+                column: 41,
+            },
+        }),
+        ..Rule::new(
+            "list",
+            seq![
+                terminal("["),
+                seq![
+                    nonterminal("item"),
+                    seq![terminal(","), nonterminal("item")].rep()
+                ]
+                .opt(),
+                terminal("]"),
+            ],
+        )
+    });
+    grammar.insert(
+        "item".to_owned(),
+        alt![
+            nonterminal("list"),
+            special("digit").times(3).except(terminal("000")),
+            empty(),
+        ],
+    );
+    grammar.set_start("item");
+    grammar
+}
+
+/// Replaces the checksum of tampered bytes with a matching one.
+fn reseal(mut bytes: Vec<u8>) -> Vec<u8> {
+    let sum = checksum(&bytes[10..]).to_le_bytes();
+    bytes[6..10].copy_from_slice(&sum);
+    bytes
+}
+
+#[test]
+fn test_round_trip() {
+    let grammar = grammar();
+    let decoded = decode(&encode(&grammar)).unwrap();
+    assert_eq!(decoded, grammar);
+    assert_eq!(decoded.rules(), grammar.rules());
+    assert_eq!(decoded.start(), Some("item"));
+
+    let empty = decode(&encode(&Grammar::new())).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_interning() {
+    let bytes = encode(&grammar());
+    let occurrences = |needle: &[u8]| {
+        bytes
+            .windows(needle.len())
+            .filter(|window| *window == needle)
+            .count()
+    };
+    assert_eq!(occurrences(b"item"), 1);
+    assert_eq!(occurrences(b"list"), 1);
+}
+
+#[test]
+fn test_header() {
+    let bytes = encode(&grammar());
+    assert_eq!(bytes[..4], MAGIC);
+    assert_eq!(decode(b"list = 'x';"), Err(DecodeError::NotAGrammar));
+
+    let mut newer = bytes.clone();
+    newer[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    assert_eq!(
+        decode(&newer),
+        Err(DecodeError::UnsupportedVersion(VERSION + 1))
+    );
+
+    let mut damaged = bytes.clone();
+    *damaged.last_mut().unwrap() ^= 1;
+    assert_eq!(decode(&damaged), Err(DecodeError::Corrupted));
+    assert_eq!(
+        decode(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::Corrupted)
+    );
+    assert_eq!(decode(&bytes[..8]), Err(DecodeError::Truncated));
+}
+
+#[test]
+fn test_contents() {
+    let bytes = encode(&grammar());
+    assert_eq!(
+        decode(&reseal(bytes[..bytes.len() - 1].to_vec())),
+        Err(DecodeError::Truncated)
+    );
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert_eq!(
+        decode(&reseal(extended)),
+        Err(DecodeError::Malformed("unexpected bytes after the grammar"))
+    );
+
+    let mut undefined = Grammar::new();
+    undefined.insert("a".to_owned(), seq![terminal("x"), nonterminal("b")]);
+    assert_eq!(
+        decode(&encode(&undefined)),
+        Err(DecodeError::UndefinedRule("b".to_owned()))
+    );
+
+    let mut single = Grammar::new();
+    single.insert("a".to_owned(), Expression::Empty);
+    let mut bytes = encode(&single);
+    // the only expression follows the one string, "a"
+    assert_eq!(bytes[10..14], [1, 1, b'a', 1]);
+    bytes[14] = 42;
+    assert_eq!(
+        decode(&reseal(bytes)),
+        Err(DecodeError::Malformed("unknown expression tag"))
+    );
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BuildError {
+    UndefinedRule(String),
+    /// The rules checked before any input is consumed, from a rule back to
+    /// itself.
+    LeftRecursion(Vec<String>),
+    MultipleDefinitions(String),
+    UnknownSpecial(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::UndefinedRule(rule) => write!(f, "rule {} is undefined", rule),
+            BuildError::LeftRecursion(chain) => write!(
+                f,
+                "rule {} is left recursive ({})",
+                chain[0],
+                chain.join(" -> ")
+            ),
+            BuildError::MultipleDefinitions(rule) => {
+                write!(f, "rule {} is multiply defined", rule)
+            }
+            BuildError::UnknownSpecial(content) => {
+                write!(f, "special sequence ?{}? is unknown", content)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
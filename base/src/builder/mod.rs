@@ -0,0 +1,202 @@
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+use super::analysis::left_recursion;
+use super::ast::{Expression, Grammar};
+use super::special::SpecialRegistry;
+use super::visit::Visitor;
+pub use error::BuildError;
+
+pub fn terminal(content: &str) -> Expression {
+    Expression::Terminal(content.to_owned())
+}
+
+pub fn nonterminal(identifier: &str) -> Expression {
+    Expression::Nonterminal(identifier.to_owned())
+}
+
+pub fn special(content: &str) -> Expression {
+    Expression::Special(content.to_owned())
+}
+
+pub fn empty() -> Expression {
+    Expression::Empty
+}
+
+/// Joins expressions into a sequence, or returns the only one. An empty
+/// sequence matches empty input.
+pub fn sequence(mut elements: Vec<Expression>) -> Expression {
+    if elements.len() < 2 {
+        return elements.pop().unwrap_or(Expression::Empty);
+    }
+    let rest = elements.split_off(2);
+    let second = elements.pop().unwrap();
+    let first = elements.pop().unwrap();
+    Expression::Sequence {
+        first: Box::new(first),
+        second: Box::new(second),
+        rest,
+    }
+}
+
+/// Joins expressions into an alternative, or returns the only one. An empty
+/// alternative matches empty input.
+pub fn alternative(mut alternatives: Vec<Expression>) -> Expression {
+    if alternatives.len() < 2 {
+        return alternatives.pop().unwrap_or(Expression::Empty);
+    }
+    let rest = alternatives.split_off(2);
+    let second = alternatives.pop().unwrap();
+    let first = alternatives.pop().unwrap();
+    Expression::Alternative {
+        first: Box::new(first),
+        second: Box::new(second),
+        rest,
+    }
+}
+
+/// Builds a sequence out of the given expressions, as in `seq![a, b, c]`.
+#[macro_export]
+macro_rules! seq {
+    ($($element:expr),* $(,)?) => {
+        $crate::builder::sequence(vec![$($element),*])
+    };
+}
+
+/// Builds an alternative out of the given expressions, as in `alt![a, b, c]`.
+#[macro_export]
+macro_rules! alt {
+    ($($alternative:expr),* $(,)?) => {
+        $crate::builder::alternative(vec![$($alternative),*])
+    };
+}
+
+impl Expression {
+    /// `[ self ]`
+    pub fn opt(self) -> Expression {
+        Expression::Optional(Box::new(self))
+    }
+
+    /// `{ self }`
+    pub fn rep(self) -> Expression {
+        Expression::Repeated(Box::new(self))
+    }
+
+    /// `count * self`
+    pub fn times(self, count: usize) -> Expression {
+        Expression::Factor {
+            count,
+            primary: Box::new(self),
+        }
+    }
+
+    /// `self - restriction`
+    pub fn except(self, restriction: Expression) -> Expression {
+        Expression::Exception {
+            subject: Box::new(self),
+            restriction: Box::new(restriction),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Accept left-recursive rules, for grammars checked with an engine that
+    /// supports them.
+    pub allow_left_recursion: bool,
+    /// Special sequences the grammar may use besides the built-in ones.
+    pub specials: SpecialRegistry,
+}
+
+/// Collects a grammar rule by rule, and validates it the way grammars read
+/// from text are validated.
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    rules: Vec<(String, Expression)>,
+    start: Option<String>,
+}
+
+/// The rules and special sequences an expression refers to.
+#[derive(Default)]
+struct References<'a> {
+    nonterminals: Vec<&'a str>,
+    specials: Vec<&'a str>,
+}
+
+impl<'a> Visitor<'a> for References<'a> {
+    fn visit_nonterminal(&mut self, identifier: &'a str) {
+        self.nonterminals.push(identifier);
+    }
+
+    fn visit_special(&mut self, content: &'a str) {
+        self.specials.push(content);
+    }
+}
+
+impl Builder {
+    pub fn new() -> Builder {
+        Builder {
+            rules: Vec::new(),
+            start: None,
+        }
+    }
+
+    /// Sets the rule to check input from, which is the first one otherwise.
+    pub fn start(mut self, name: &str) -> Builder {
+        self.start = Some(name.to_owned());
+        self
+    }
+
+    /// Adds a rule. Rules are validated in the order they are added.
+    pub fn rule(mut self, name: &str, expression: Expression) -> Builder {
+        self.rules.push((name.to_owned(), expression));
+        self
+    }
+
+    pub fn build(self) -> Result<Grammar, Vec<BuildError>> {
+        self.build_with(&Options::default())
+    }
+
+    /// Returns the grammar, or every error found in it: rules defined more
+    /// than once, undefined rules, unknown special sequences and, unless
+    /// allowed, left recursion.
+    pub fn build_with(self, options: &Options) -> Result<Grammar, Vec<BuildError>> {
+        let mut errors = Vec::new();
+        let mut grammar = Grammar::new();
+        for (name, expression) in self.rules.iter() {
+            if grammar.insert(name.clone(), expression.clone()).is_some() {
+                errors.push(BuildError::MultipleDefinitions(name.clone()));
+            }
+        }
+        if let Some(start) = &self.start {
+            if !grammar.contains_key(start) {
+                errors.push(BuildError::UndefinedRule(start.clone()));
+            }
+            grammar.set_start(start);
+        }
+        for (_, expression) in self.rules.iter() {
+            let mut references = References::default();
+            references.visit_expression(expression);
+            for identifier in references.nonterminals {
+                if !grammar.contains_key(identifier) {
+                    errors.push(BuildError::UndefinedRule(identifier.to_owned()));
+                }
+            }
+            for content in references.specials {
+                if !options.specials.contains(content) {
+                    errors.push(BuildError::UnknownSpecial(content.to_owned()));
+                }
+            }
+        }
+        if !options.allow_left_recursion {
+            let chains = left_recursion(&grammar, &options.specials);
+            errors.extend(chains.into_iter().map(BuildError::LeftRecursion));
+        }
+        if errors.is_empty() {
+            Ok(grammar)
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use super::{empty, nonterminal, special, terminal, BuildError, Builder, Options};
+use crate::ast::Expression;
+use crate::checker::{self, check};
+use crate::special::SpecialRegistry;
+use crate::{alt, seq};
+
+#[test]
+fn test_combinators() {
+    assert_eq!(seq![], Expression::Empty);
+    assert_eq!(alt![terminal("a")], terminal("a"));
+    assert_eq!(
+        alt![
+            seq![terminal("a"), nonterminal("b")],
+            empty(),
+            terminal("c").opt()
+        ],
+        Expression::Alternative {
+            first: Box::new(Expression::Sequence {
+                first: Box::new(terminal("a")),
+                second: Box::new(nonterminal("b")),
+                rest: vec![],
+            }),
+            second: Box::new(Expression::Empty),
+            rest: vec![Expression::Optional(Box::new(terminal("c")))],
+        }
+    );
+    assert_eq!(
+        terminal("a").times(2).except(terminal("b").rep()),
+        Expression::Exception {
+            subject: Box::new(Expression::Factor {
+                count: 2,
+                primary: Box::new(terminal("a")),
+            }),
+            restriction: Box::new(Expression::Repeated(Box::new(terminal("b")))),
+        }
+    );
+}
+
+#[test]
+fn test_build() {
+    let grammar = Builder::new()
+        .rule(
+            "list",
+            seq![
+                terminal("["),
+                nonterminal("item").opt(),
+                seq![terminal(","), nonterminal("item")].rep(),
+                terminal("]"),
+            ],
+        )
+        .rule("item", alt![nonterminal("list"), special("digit")])
+        .build()
+        .unwrap();
+    assert_eq!(grammar.keys().collect::<Vec<_>>(), vec!["list", "item"]);
+    assert_eq!(grammar.start(), Some("list"));
+    assert!(check("[1,[],[2,3]]", &grammar, "list").is_ok());
+    assert!(check("[1,]", &grammar, "list").is_err());
+}
+
+#[test]
+fn test_errors() {
+    let errors = Builder::new()
+        .rule("a", seq![nonterminal("b"), terminal("x")])
+        .rule("b", alt![nonterminal("c").opt(), nonterminal("a")])
+        .rule("c", seq![nonterminal("d"), special("unknown")])
+        .rule("c", terminal("y"))
+        .start("e")
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        errors,
+        vec![
+            BuildError::MultipleDefinitions("c".to_owned()),
+            BuildError::UndefinedRule("e".to_owned()),
+            BuildError::UndefinedRule("d".to_owned()),
+            BuildError::UnknownSpecial("unknown".to_owned()),
+            BuildError::LeftRecursion(vec!["a".to_owned(), "b".to_owned(), "a".to_owned()]),
+            BuildError::LeftRecursion(vec!["b".to_owned(), "a".to_owned(), "b".to_owned()]),
+        ]
+    );
+    assert_eq!(
+        errors[4].to_string(),
+        "rule a is left recursive (a -> b -> a)"
+    );
+}
+
+#[test]
+fn test_options() {
+    let mut specials = SpecialRegistry::new();
+    specials.insert("vowel", |input| {
+        input
+            .chars()
+            .next()
+            .filter(|ch| "aeiou".contains(*ch))
+            .map(|ch| (ch.len_utf8(), None))
+    });
+    let options = Options {
+        allow_left_recursion: true,
+        specials,
+    };
+    let grammar = Builder::new()
+        .rule(
+            "a",
+            alt![seq![nonterminal("a"), special("vowel")], terminal("x")],
+        )
+        .build_with(&options);
+    assert!(grammar.is_ok());
+}
+
+#[test]
+fn test_nullable_special_sequences() {
+    let recursive = || alt![seq![special("spaces"), nonterminal("a")], terminal("x")];
+    let left_recursion = BuildError::LeftRecursion(vec!["a".to_owned(), "a".to_owned()]);
+    let errors = Builder::new()
+        .rule(
+            "a",
+            alt![seq![special("end of input"), nonterminal("a")], terminal("x")],
+        )
+        .build()
+        .unwrap_err();
+    assert_eq!(errors, vec![left_recursion.clone()]);
+
+    let spaces = |input: &str| Some((input.len() - input.trim_start().len(), None));
+    let mut options = Options::default();
+    options.specials.insert("spaces", spaces);
+    let errors = Builder::new()
+        .rule("a", recursive())
+        .build_with(&options)
+        .unwrap_err();
+    assert_eq!(errors, vec![left_recursion]);
+
+    options.specials.insert_consuming("spaces", spaces);
+    let grammar = Builder::new()
+        .rule("a", recursive())
+        .build_with(&options)
+        .unwrap();
+    let options = checker::Options {
+        specials: options.specials,
+        ..checker::Options::default()
+    };
+    assert!(checker::check_with("  x", &grammar, "a", &options).is_ok());
+}
@@ -0,0 +1,54 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expected {
+    Terminal(String),
+    Rule(String),
+    Special(String),
+    EndOfInput,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Terminal(content) => write!(f, "'{}'", content),
+            Expected::Rule(identifier) => write!(f, "{}", identifier),
+            Expected::Special(content) => write!(f, "?{}?", content),
+            Expected::EndOfInput => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CheckError {
+    /// Byte offset of the furthest position the checker reached.
+    pub position: usize,
+    /// Everything that would have been accepted at `position`.
+    pub expected: Vec<Expected>,
+    /// Productions that were being checked when `position` was reached,
+    /// outermost first.
+    pub stack: Vec<String>,
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected = self
+            .expected
+            .iter()
+            .map(|expected| expected.to_string())
+            .collect::<Vec<_>>();
+        match expected.split_last() {
+            None => write!(f, "unexpected input at position {}", self.position),
+            Some((last, [])) => write!(f, "expected {} at position {}", last, self.position),
+            Some((last, init)) => write!(
+                f,
+                "expected {} or {} at position {}",
+                init.join(", "),
+                last,
+                self.position
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
@@ -1,12 +1,107 @@
+pub mod error;
 pub mod node;
+#[cfg(test)]
+mod tests;
 
 use super::ast::{Expression, Grammar};
+use super::earley;
+use super::span::Locator;
+use super::special::{self, SpecialRegistry};
+pub use error::{CheckError, Expected};
 pub use node::Node;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Engine {
+    /// Recursive descent with ordered choice, the first successful
+    /// alternative wins.
+    #[default]
+    Backtracking,
+    /// Earley recognition, accepting every string the grammar describes,
+    /// including through left-recursive and ambiguous rules.
+    Earley,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub engine: Engine,
+    /// Memoize the result of every rule at every input position, so that no
+    /// rule is checked twice at the same position, at the cost of memory. A
+    /// memoized result is copied each time it's reused, tree included, so
+    /// checking still takes time proportional to the size of those trees.
+    pub packrat: bool,
+    /// Maximum number of entries kept in the memo table. Once the table is
+    /// full no new results are memoized. Unbounded when `None`.
+    pub memo_capacity: Option<usize>,
+    /// Matchers for special sequences other than the built-in ones.
+    pub specials: SpecialRegistry,
+}
+
+type Memo<'a> = HashMap<(&'a str, usize), Result<(usize, String, Node), ()>>;
+
+struct Context<'a> {
+    input: &'a str,
+    locator: Locator<'a>,
+    grammar: &'a Grammar,
+    options: &'a Options,
+    memo: Memo<'a>,
+    stack: Vec<String>,
+    furthest: usize,
+    expected: Vec<Expected>,
+    furthest_stack: Vec<String>,
+}
+
+impl<'a> Context<'a> {
+    fn new(input: &'a str, grammar: &'a Grammar, options: &'a Options) -> Context<'a> {
+        Context {
+            input,
+            locator: Locator::new(input),
+            grammar,
+            options,
+            memo: HashMap::new(),
+            stack: Vec::new(),
+            furthest: 0,
+            expected: Vec::new(),
+            furthest_stack: Vec::new(),
+        }
+    }
+
+    fn position(&self, rest: &str) -> usize {
+        self.input.len() - rest.len()
+    }
+
+    fn memo_full(&self) -> bool {
+        match self.options.memo_capacity {
+            Some(capacity) => self.memo.len() >= capacity,
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, rest: &str, expected: Expected) {
+        let position = self.position(rest);
+        if position > self.furthest || self.expected.is_empty() {
+            self.furthest = position;
+            self.expected.clear();
+            self.furthest_stack = self.stack.clone();
+        }
+        if position == self.furthest && !self.expected.contains(&expected) {
+            self.expected.push(expected);
+        }
+    }
+
+    fn error(self) -> CheckError {
+        CheckError {
+            position: self.furthest,
+            expected: self.expected,
+            stack: self.furthest_stack,
+        }
+    }
+}
 
 fn check_expr<'a>(
     input: &'a str,
     expression: &'a Expression,
-    grammar: &'a Grammar,
+    context: &mut Context<'a>,
 ) -> Result<(&'a str, String, Option<Vec<Node>>), ()> {
     match &expression {
         Expression::Alternative {
@@ -14,17 +109,17 @@ fn check_expr<'a>(
             second: box second,
             rest,
         } => {
-            match check_expr(input, first, grammar) {
+            match check_expr(input, first, context) {
                 Ok((rest, output, node)) => return Ok((rest, output, node)),
                 Err(()) => {}
             }
-            match check_expr(input, second, grammar) {
+            match check_expr(input, second, context) {
                 Ok((rest, output, node)) => return Ok((rest, output, node)),
                 Err(()) => {}
             }
             for expression in rest.iter()
             {
-                match check_expr(input, expression, grammar) {
+                match check_expr(input, expression, context) {
                     Ok((rest, output, node)) => return Ok((rest, output, node)),
                     Err(()) => {}
                 }
@@ -39,13 +134,13 @@ fn check_expr<'a>(
             let mut input = input;
             let mut output = String::new();
             let mut nodes = Vec::new();
-            let (inp, output_first, node_first) = check_expr(input, first, grammar)?;
+            let (inp, output_first, node_first) = check_expr(input, first, context)?;
             input = inp;
             output.push_str(&output_first);
             if let Some(n) = node_first {
                 nodes.extend(n);
             }
-            let (inp, output_second, node_second) = check_expr(input, second, grammar)?;
+            let (inp, output_second, node_second) = check_expr(input, second, context)?;
             input = inp;
             output.push_str(&output_second);
             if let Some(n) = node_second {
@@ -53,7 +148,7 @@ fn check_expr<'a>(
             }
             for expression in rest.iter()
             {
-                let (inp, output_expr, node_expr) = check_expr(input, expression, grammar)?;
+                let (inp, output_expr, node_expr) = check_expr(input, expression, context)?;
                 input = inp;
                 output.push_str(&output_expr);
                 if let Some(n) = node_expr {
@@ -63,7 +158,7 @@ fn check_expr<'a>(
             Ok((input, output, Some(nodes)))
         }
         Expression::Optional(box inner) => {
-            match check_expr(input, inner, grammar) {
+            match check_expr(input, inner, context) {
                 Ok((rest, output, nodes)) => Ok((rest, output, nodes)),
                 Err(()) => Ok((input, String::new(), None)),
             }
@@ -73,7 +168,11 @@ fn check_expr<'a>(
             let mut output = String::new();
             let mut nodes = Vec::new();
             loop {
-                match check_expr(input, inner, grammar) {
+                match check_expr(input, inner, context) {
+                    // an iteration that consumes nothing would repeat forever
+                    Ok((rest, ..)) if rest.len() == input.len() => {
+                        return Ok((input, output, Some(nodes)))
+                    }
                     Ok((rest, out, node_expr)) => {
                         input = rest;
                         output.push_str(&out);
@@ -93,7 +192,7 @@ fn check_expr<'a>(
             let mut output = String::new();
             let mut nodes = Vec::new();
             for _ in 0..*count {
-                match check_expr(input, primary, grammar) {
+                match check_expr(input, primary, context) {
                     Ok((rest, out, node_expr)) => {
                         input = rest;
                         output.push_str(&out);
@@ -110,46 +209,131 @@ fn check_expr<'a>(
             subject: box subject,
             restriction: box restriction,
         } => {
-            let (input, read_chars, node) = check_expr(input, subject, grammar)?;
-            match check_expr(&read_chars, restriction, grammar) {
+            let (input, read_chars, node) = check_expr(input, subject, context)?;
+            // the restriction is checked against a fresh string, so it cannot share
+            // the memo table with the rest of the input
+            let options = Options {
+                packrat: false,
+                ..context.options.clone()
+            };
+            let mut restriction_context = Context::new(&read_chars, context.grammar, &options);
+            match check_expr(&read_chars, restriction, &mut restriction_context) {
                 Ok((_, matched_chars, _)) if matched_chars == read_chars => Err(()),
                 _ => Ok((input, read_chars, node)),
             }
         }
         Expression::Nonterminal(identifier) => {
-            let (rest, out, node) = check_prod(input, grammar, identifier)?;
+            let (rest, out, node) = check_prod(input, identifier, context)?;
             return Ok((rest, out, Some(vec![node])));
         },
         Expression::Terminal(content) => {
             if input.starts_with(content) {
                 let len = content.len();
-                Ok((&input[len..], content.clone(), Some(vec![Node::Terminal(content.clone())])))
+                let from = context.position(input);
+                let span = context.locator.span(from, from + len);
+                Ok((&input[len..], content.clone(), Some(vec![Node::Terminal(content.clone(), span)])))
             } else {
+                context.expect(input, Expected::Terminal(content.clone()));
                 Err(())
             }
         }
-        Expression::Special(_) => Err(()),
+        Expression::Special(content) => {
+            match context.options.specials.matches(content, input) {
+                Some(matched @ (len, _)) => {
+                    let from = context.position(input);
+                    let nodes = special::matched_nodes(context.input, &context.locator, from, matched);
+                    Ok((&input[len..], input[..len].to_owned(), nodes))
+                }
+                None => {
+                    context.expect(input, Expected::Special(content.clone()));
+                    Err(())
+                }
+            }
+        }
         Expression::Empty => Ok((input, String::new(), None)),
     }
 }
 
-pub(super) fn check_prod<'a>(
+fn check_prod<'a>(
+    input: &'a str,
+    initial_rule: &'a str,
+    context: &mut Context<'a>,
+) -> Result<(&'a str, String, Node), ()> {
+    if !context.options.packrat {
+        return check_rule(input, initial_rule, context);
+    }
+
+    let position = context.position(input);
+    if let Some(result) = context.memo.get(&(initial_rule, position)) {
+        return match result {
+            Ok((end, out, node)) => Ok((&context.input[*end..], out.clone(), node.clone())),
+            Err(()) => Err(()),
+        };
+    }
+
+    let result = check_rule(input, initial_rule, context);
+    if !context.memo_full() {
+        let entry = match &result {
+            Ok((rest, out, node)) => Ok((context.position(rest), out.clone(), node.clone())),
+            Err(()) => Err(()),
+        };
+        context.memo.insert((initial_rule, position), entry);
+    }
+    result
+}
+
+fn check_rule<'a>(
     input: &'a str,
-    grammar: &'a Grammar,
     initial_rule: &'a str,
+    context: &mut Context<'a>,
 ) -> Result<(&'a str, String, Node), ()> {
-    let expression = grammar.get(initial_rule).unwrap();
-    let (rest, out, nodes) = check_expr(input, expression, grammar)?;
-    return Ok((rest, out, Node::Nonterminal(initial_rule.to_owned(), nodes.unwrap_or(Vec::new()))));
+    // a grammar built without validation may use rules it doesn't define
+    let expression = match context.grammar.get(initial_rule) {
+        Some(expression) => expression,
+        None => {
+            context.expect(input, Expected::Rule(initial_rule.to_owned()));
+            return Err(());
+        }
+    };
+    context.stack.push(initial_rule.to_owned());
+    let result = check_expr(input, expression, context);
+    context.stack.pop();
+    let (rest, out, nodes) = match result {
+        Ok(result) => result,
+        Err(()) => {
+            context.expect(input, Expected::Rule(initial_rule.to_owned()));
+            return Err(());
+        }
+    };
+    let span = context.locator.span(context.position(input), context.position(rest));
+    return Ok((rest, out, Node::Nonterminal(initial_rule.to_owned(), nodes.unwrap_or(Vec::new()), span)));
 }
 
 pub fn check<'a>(
     input: &'a str,
     grammar: &'a Grammar,
     initial_rule: &'a str,
-) -> Option<Node> {
-    match check_prod(input, grammar, initial_rule) {
-        Ok((input, _, node)) if input.is_empty() => Some(node),
-        _ => None,
+) -> Result<Node, CheckError> {
+    check_with(input, grammar, initial_rule, &Options::default())
+}
+
+pub fn check_with<'a>(
+    input: &'a str,
+    grammar: &'a Grammar,
+    initial_rule: &'a str,
+    options: &'a Options,
+) -> Result<Node, CheckError> {
+    if options.engine == Engine::Earley {
+        return earley::check(input, grammar, initial_rule, options);
+    }
+
+    let mut context = Context::new(input, grammar, options);
+    match check_prod(input, initial_rule, &mut context) {
+        Ok((input, _, node)) if input.is_empty() => Ok(node),
+        Ok((input, _, _)) => {
+            context.expect(input, Expected::EndOfInput);
+            Err(context.error())
+        }
+        Err(()) => Err(context.error()),
     }
 }
@@ -1,5 +1,17 @@
-#[derive(Clone)]
+use crate::span::Span;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Node {
-    Nonterminal(String, Vec<Node>),
-    Terminal(String),
+    Nonterminal(String, Vec<Node>, Span),
+    Terminal(String, Span),
+}
+
+impl Node {
+    /// The stretch of the checked input this node matched.
+    pub fn span(&self) -> Span {
+        match self {
+            Node::Nonterminal(_, _, span) => *span,
+            Node::Terminal(_, span) => *span,
+        }
+    }
 }
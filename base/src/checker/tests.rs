@@ -0,0 +1,408 @@
+use super::{check, check_with, CheckError, Engine, Expected, Node, Options};
+use crate::ast::{Expression, Grammar};
+use crate::builder::{nonterminal, terminal};
+use crate::span::{Position, Span};
+use crate::special::SpecialRegistry;
+use crate::testing::{branch, leaf, strip};
+
+// s = a, 'x' | a, 'y';
+// a = '(', s, ')' | 'z';
+fn backtracking_grammar() -> Grammar {
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "s".to_owned(),
+        Expression::Alternative {
+            first: Box::new(Expression::Sequence {
+                first: Box::new(nonterminal("a")),
+                second: Box::new(terminal("x")),
+                rest: vec![],
+            }),
+            second: Box::new(Expression::Sequence {
+                first: Box::new(nonterminal("a")),
+                second: Box::new(terminal("y")),
+                rest: vec![],
+            }),
+            rest: vec![],
+        },
+    );
+    grammar.insert(
+        "a".to_owned(),
+        Expression::Alternative {
+            first: Box::new(Expression::Sequence {
+                first: Box::new(terminal("(")),
+                second: Box::new(nonterminal("s")),
+                rest: vec![terminal(")")],
+            }),
+            second: Box::new(terminal("z")),
+            rest: vec![],
+        },
+    );
+    grammar
+}
+
+fn nested(depth: usize) -> String {
+    format!("{}z{}", "(".repeat(depth), "y)".repeat(depth)) + "y"
+}
+
+#[test]
+fn test_packrat_agrees_with_backtracking() {
+    let grammar = backtracking_grammar();
+    let options = Options {
+        packrat: true,
+        ..Options::default()
+    };
+    for input in &["zx", "zy", "(zx)y", "(zy)x", "((zy)x)y", "(zy", "zz", ""] {
+        assert_eq!(
+            check(input, &grammar, "s"),
+            check_with(input, &grammar, "s", &options)
+        );
+    }
+    assert_eq!(
+        check_with("(zx)y", &grammar, "s", &options).map(strip),
+        Ok(branch(
+            "s",
+            vec![
+                branch(
+                    "a",
+                    vec![
+                        leaf("("),
+                        branch("s", vec![branch("a", vec![leaf("z")]), leaf("x")]),
+                        leaf(")"),
+                    ]
+                ),
+                leaf("y"),
+            ]
+        ))
+    );
+}
+
+#[test]
+fn test_packrat_deep_nesting() {
+    let grammar = backtracking_grammar();
+    let options = Options {
+        packrat: true,
+        ..Options::default()
+    };
+    assert!(check_with(&nested(40), &grammar, "s", &options).is_ok());
+    assert!(check_with(&nested(40)[1..], &grammar, "s", &options).is_err());
+}
+
+#[test]
+fn test_packrat_bounded_memo() {
+    let grammar = backtracking_grammar();
+    let options = Options {
+        packrat: true,
+        memo_capacity: Some(4),
+        ..Options::default()
+    };
+    assert_eq!(
+        check(&nested(8), &grammar, "s"),
+        check_with(&nested(8), &grammar, "s", &options)
+    );
+    assert!(check_with(&nested(8), &grammar, "s", &options).is_ok());
+}
+
+#[test]
+fn test_error_furthest_position() {
+    let grammar = backtracking_grammar();
+    assert_eq!(
+        check("(zx)z", &grammar, "s"),
+        Err(CheckError {
+            position: 4,
+            expected: vec![
+                Expected::Terminal("x".to_owned()),
+                Expected::Terminal("y".to_owned()),
+            ],
+            stack: vec!["s".to_owned()],
+        })
+    );
+    assert_eq!(
+        check("(z", &grammar, "s"),
+        Err(CheckError {
+            position: 2,
+            expected: vec![
+                Expected::Terminal("x".to_owned()),
+                Expected::Terminal("y".to_owned()),
+            ],
+            stack: vec!["s".to_owned(), "a".to_owned(), "s".to_owned()],
+        })
+    );
+}
+
+#[test]
+fn test_error_trailing_input() {
+    let grammar = backtracking_grammar();
+    let error = check("zxz", &grammar, "s").unwrap_err();
+    assert_eq!(error.position, 2);
+    assert_eq!(error.expected, vec![Expected::EndOfInput]);
+    assert_eq!(error.to_string(), "expected end of input at position 2");
+}
+
+#[test]
+fn test_error_expected_rules() {
+    let grammar = backtracking_grammar();
+    let error = check("q", &grammar, "s").unwrap_err();
+    assert_eq!(error.position, 0);
+    assert_eq!(
+        error.expected,
+        vec![
+            Expected::Terminal("(".to_owned()),
+            Expected::Terminal("z".to_owned()),
+            Expected::Rule("a".to_owned()),
+            Expected::Rule("s".to_owned()),
+        ]
+    );
+    assert_eq!(error.to_string(), "expected '(', 'z', a or s at position 0");
+}
+
+#[test]
+fn test_error_undefined_rule() {
+    let grammar = backtracking_grammar();
+    let error = CheckError {
+        position: 0,
+        expected: vec![Expected::Rule("t".to_owned())],
+        stack: Vec::new(),
+    };
+    for engine in [Engine::Backtracking, Engine::Earley] {
+        for packrat in [false, true] {
+            let options = Options {
+                engine,
+                packrat,
+                ..Options::default()
+            };
+            assert_eq!(check_with("zx", &grammar, "t", &options), Err(error.clone()));
+        }
+    }
+}
+
+#[test]
+fn test_node_spans() {
+    // s = 'é', { '\n' }, a;
+    // a = 'x';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "s".to_owned(),
+        Expression::Sequence {
+            first: Box::new(terminal("é")),
+            second: Box::new(Expression::Repeated(Box::new(terminal("\n")))),
+            rest: vec![nonterminal("a")],
+        },
+    );
+    grammar.insert("a".to_owned(), terminal("x"));
+    let position = |offset, char, line, column| Position {
+        offset,
+        char,
+        line,
+        column,
+    };
+    let node = check("é\n\nx", &grammar, "s").unwrap();
+    assert_eq!(
+        node.span(),
+        Span {
+            from: position(0, 0, 0, 0),
+            to: position(5, 4, 2, 1),
+        }
+    );
+    let children = match node {
+        Node::Nonterminal(_, children, _) => children,
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        children.iter().map(Node::span).collect::<Vec<_>>(),
+        vec![
+            Span {
+                from: position(0, 0, 0, 0),
+                to: position(2, 1, 0, 1),
+            },
+            Span {
+                from: position(2, 1, 0, 1),
+                to: position(3, 2, 1, 0),
+            },
+            Span {
+                from: position(3, 2, 1, 0),
+                to: position(4, 3, 2, 0),
+            },
+            Span {
+                from: position(4, 3, 2, 0),
+                to: position(5, 4, 2, 1),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_long_line_spans() {
+    // s = { 'é' | '\n' }, 'x';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "s".to_owned(),
+        Expression::Sequence {
+            first: Box::new(Expression::Repeated(Box::new(Expression::Alternative {
+                first: Box::new(terminal("é")),
+                second: Box::new(terminal("\n")),
+                rest: vec![],
+            }))),
+            second: Box::new(terminal("x")),
+            rest: vec![],
+        },
+    );
+    let input = format!("é\n{}x", "é".repeat(99));
+    let node = check(&input, &grammar, "s").unwrap();
+    let last = match node {
+        Node::Nonterminal(_, children, _) => children.last().unwrap().span(),
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        last.from,
+        Position {
+            offset: 201,
+            char: 101,
+            line: 1,
+            column: 99,
+        }
+    );
+}
+
+#[test]
+fn test_builtin_special_sequences() {
+    // s = { ? letter ? | ? digit ? }, ? whitespace ?, ? Lu ?, ? end of input ?;
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "s".to_owned(),
+        Expression::Sequence {
+            first: Box::new(Expression::Repeated(Box::new(Expression::Alternative {
+                first: Box::new(Expression::Special(" letter ".to_owned())),
+                second: Box::new(Expression::Special(" digit ".to_owned())),
+                rest: vec![],
+            }))),
+            second: Box::new(Expression::Special(" whitespace ".to_owned())),
+            rest: vec![
+                Expression::Special(" Lu ".to_owned()),
+                Expression::Special(" end of input ".to_owned()),
+            ],
+        },
+    );
+    assert_eq!(
+        check("é1 Ä", &grammar, "s").map(strip),
+        Ok(branch("s", vec![leaf("é"), leaf("1"), leaf(" "), leaf("Ä")]))
+    );
+    assert_eq!(
+        check("é1 ä", &grammar, "s"),
+        Err(CheckError {
+            position: 4,
+            expected: vec![Expected::Special(" Lu ".to_owned())],
+            stack: vec!["s".to_owned()],
+        })
+    );
+}
+
+#[test]
+fn test_registered_special_sequences() {
+    // s = ? word ?, ' ', ? number ?;
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "s".to_owned(),
+        Expression::Sequence {
+            first: Box::new(Expression::Special(" word ".to_owned())),
+            second: Box::new(terminal(" ")),
+            rest: vec![Expression::Special(" number ".to_owned())],
+        },
+    );
+    let mut specials = SpecialRegistry::new();
+    specials.insert("word", |input| {
+        let len = input.find(|ch: char| !ch.is_ascii_alphabetic()).unwrap_or(input.len());
+        match &input[..len] {
+            "" | "if" => None,
+            _ => Some((len, None)),
+        }
+    });
+    specials.insert("Number", |input| {
+        let len = input.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(input.len());
+        let span = |to| Span {
+            from: Position::default(),
+            to: Position {
+                offset: to,
+                ..Position::default()
+            },
+        };
+        let digits = Node::Terminal(input[..len].to_owned(), span(len));
+        Some((len, Some(Node::Nonterminal("number".to_owned(), vec![digits], span(len)))))
+    });
+    for engine in [Engine::Backtracking, Engine::Earley] {
+        let options = Options {
+            engine,
+            specials: specials.clone(),
+            ..Options::default()
+        };
+        let node = check_with("x 42", &grammar, "s", &options).unwrap();
+        assert_eq!(
+            strip(node.clone()),
+            branch("s", vec![leaf("x"), leaf(" "), branch("number", vec![leaf("42")])])
+        );
+        if let Node::Nonterminal(_, children, _) = node {
+            assert_eq!(children[2].span().from.offset, 2);
+            assert_eq!(children[2].span().to.column, 4);
+        }
+        assert_eq!(
+            check_with("if 42", &grammar, "s", &options).map_err(|e| e.position),
+            Err(0)
+        );
+    }
+    assert!(check("x 42", &grammar, "s").is_err());
+}
+
+#[test]
+fn test_registered_special_spans() {
+    let mut grammar = Grammar::new();
+    grammar.insert("s".to_owned(), Expression::Special("accent".to_owned()));
+    let mut options = Options::default();
+    // spans past the match, or within a character, are clamped to the match
+    options.specials.insert("accent", |input| {
+        let position = |offset| Position {
+            offset,
+            ..Position::default()
+        };
+        let span = Span {
+            from: position(1),
+            to: position(100),
+        };
+        let content = input.get(..2).filter(|content| *content == "é")?;
+        Some((2, Some(Node::Terminal(content.to_owned(), span))))
+    });
+    for engine in [Engine::Backtracking, Engine::Earley] {
+        options.engine = engine;
+        let node = check_with("é", &grammar, "s", &options).unwrap();
+        if let Node::Nonterminal(_, children, _) = node {
+            assert_eq!(children[0].span().from.offset, 0);
+            assert_eq!(children[0].span().to.offset, 2);
+            assert_eq!(children[0].span().to.column, 1);
+        }
+    }
+}
+
+#[test]
+fn test_nullable_repetition() {
+    // s = { [ 'a' ] }, 'b';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "s".to_owned(),
+        Expression::Sequence {
+            first: Box::new(Expression::Repeated(Box::new(Expression::Optional(Box::new(
+                terminal("a"),
+            ))))),
+            second: Box::new(terminal("b")),
+            rest: vec![],
+        },
+    );
+    for engine in [Engine::Backtracking, Engine::Earley] {
+        let options = Options {
+            engine,
+            ..Options::default()
+        };
+        assert_eq!(
+            check_with("aab", &grammar, "s", &options).map(strip),
+            Ok(branch("s", vec![leaf("a"), leaf("a"), leaf("b")]))
+        );
+        assert!(check_with("aac", &grammar, "s", &options).is_err());
+    }
+}
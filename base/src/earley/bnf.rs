@@ -0,0 +1,202 @@
+use crate::ast::{Expression, Grammar};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(super) enum Symbol {
+    Nonterminal(usize),
+    Terminal(String),
+    Special(String),
+}
+
+#[derive(Debug)]
+pub(super) struct Rule {
+    pub lhs: usize,
+    pub rhs: Vec<Symbol>,
+}
+
+#[derive(Debug)]
+pub(super) enum Kind {
+    /// A production rule of the original grammar.
+    Named(String),
+    /// A nonterminal introduced while lowering an expression, whose children
+    /// are spliced into the enclosing node.
+    Auxiliary,
+    /// Like `Auxiliary`, but only derives strings that the restriction
+    /// nonterminal does not derive.
+    Exception(usize),
+}
+
+/// A plain context-free grammar obtained by lowering every EBNF operator
+/// into auxiliary nonterminals.
+#[derive(Debug)]
+pub(super) struct Bnf {
+    pub kinds: Vec<Kind>,
+    pub rules: Vec<Rule>,
+    pub alternatives: Vec<Vec<usize>>,
+    /// Whether each nonterminal surely derives empty input, which lets the
+    /// chart skip over it right away. Unlike the nullability the grammar is
+    /// validated with, this must not err on the side of nullable, so special
+    /// sequences and exceptions are left to be completed in the chart.
+    pub nullable: Vec<bool>,
+    /// The production rule each nonterminal was lowered from.
+    pub owners: Vec<usize>,
+    names: HashMap<String, usize>,
+    owner: usize,
+}
+
+impl Bnf {
+    pub fn new(grammar: &Grammar) -> Bnf {
+        let mut bnf = Bnf {
+            kinds: Vec::new(),
+            rules: Vec::new(),
+            alternatives: Vec::new(),
+            nullable: Vec::new(),
+            owners: Vec::new(),
+            names: HashMap::new(),
+            owner: 0,
+        };
+        for name in grammar.keys() {
+            bnf.owner = bnf.kinds.len();
+            let nonterminal = bnf.nonterminal(Kind::Named(name.clone()));
+            bnf.names.insert(name.clone(), nonterminal);
+        }
+        for (name, expression) in grammar.iter() {
+            let lhs = bnf.names[name];
+            bnf.owner = lhs;
+            let rhs = bnf.lower(expression);
+            bnf.rule(lhs, rhs);
+        }
+        bnf.compute_nullable();
+        bnf
+    }
+
+    pub fn named(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    fn nonterminal(&mut self, kind: Kind) -> usize {
+        self.kinds.push(kind);
+        self.alternatives.push(Vec::new());
+        self.nullable.push(false);
+        self.owners.push(self.owner);
+        self.kinds.len() - 1
+    }
+
+    fn rule(&mut self, lhs: usize, rhs: Vec<Symbol>) {
+        self.rules.push(Rule { lhs, rhs });
+        self.alternatives[lhs].push(self.rules.len() - 1);
+    }
+
+    fn auxiliary(&mut self, alternatives: Vec<Vec<Symbol>>) -> Vec<Symbol> {
+        let nonterminal = self.nonterminal(Kind::Auxiliary);
+        for rhs in alternatives {
+            self.rule(nonterminal, rhs);
+        }
+        vec![Symbol::Nonterminal(nonterminal)]
+    }
+
+    fn lower(&mut self, expression: &Expression) -> Vec<Symbol> {
+        match expression {
+            Expression::Alternative {
+                first: box first,
+                second: box second,
+                rest,
+            } => {
+                let mut alternatives = vec![self.lower(first), self.lower(second)];
+                for expression in rest.iter() {
+                    alternatives.push(self.lower(expression));
+                }
+                self.auxiliary(alternatives)
+            }
+            Expression::Sequence {
+                first: box first,
+                second: box second,
+                rest,
+            } => {
+                let mut symbols = self.lower(first);
+                symbols.extend(self.lower(second));
+                for expression in rest.iter() {
+                    symbols.extend(self.lower(expression));
+                }
+                symbols
+            }
+            Expression::Optional(box inner) => {
+                let inner = self.lower(inner);
+                self.auxiliary(vec![inner, Vec::new()])
+            }
+            Expression::Repeated(box inner) => {
+                let inner = self.lower(inner);
+                let nonterminal = self.nonterminal(Kind::Auxiliary);
+                let mut rhs = vec![Symbol::Nonterminal(nonterminal)];
+                rhs.extend(inner);
+                self.rule(nonterminal, Vec::new());
+                self.rule(nonterminal, rhs);
+                vec![Symbol::Nonterminal(nonterminal)]
+            }
+            Expression::Factor {
+                count,
+                primary: box primary,
+            } => {
+                let primary = self.lower(primary);
+                let mut symbols = Vec::new();
+                for _ in 0..*count {
+                    symbols.extend(primary.iter().cloned());
+                }
+                symbols
+            }
+            Expression::Exception {
+                subject: box subject,
+                restriction: box restriction,
+            } => {
+                let restriction = self.lower(restriction);
+                let restriction = match restriction.as_slice() {
+                    [Symbol::Nonterminal(nonterminal)] => *nonterminal,
+                    _ => {
+                        let nonterminal = self.nonterminal(Kind::Auxiliary);
+                        self.rule(nonterminal, restriction);
+                        nonterminal
+                    }
+                };
+                let subject = self.lower(subject);
+                let nonterminal = self.nonterminal(Kind::Exception(restriction));
+                self.rule(nonterminal, subject);
+                vec![Symbol::Nonterminal(nonterminal)]
+            }
+            Expression::Nonterminal(identifier) => match self.names.get(identifier) {
+                Some(nonterminal) => vec![Symbol::Nonterminal(*nonterminal)],
+                // an undefined rule derives nothing
+                None => vec![Symbol::Nonterminal(self.nonterminal(Kind::Auxiliary))],
+            },
+            Expression::Terminal(content) => vec![Symbol::Terminal(content.clone())],
+            Expression::Special(content) => vec![Symbol::Special(content.clone())],
+            Expression::Empty => Vec::new(),
+        }
+    }
+
+    fn compute_nullable(&mut self) {
+        loop {
+            let mut changed = false;
+            for nonterminal in 0..self.kinds.len() {
+                if self.nullable[nonterminal] {
+                    continue;
+                }
+                if let Kind::Exception(_) = self.kinds[nonterminal] {
+                    continue;
+                }
+                let derives_empty = self.alternatives[nonterminal].iter().any(|&rule| {
+                    self.rules[rule].rhs.iter().all(|symbol| match symbol {
+                        Symbol::Nonterminal(inner) => self.nullable[*inner],
+                        _ => false,
+                    })
+                });
+                if derives_empty {
+                    self.nullable[nonterminal] = true;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+}
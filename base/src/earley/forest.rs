@@ -0,0 +1,394 @@
+use super::bnf::{Kind, Symbol};
+use super::{Chart, Item};
+use crate::checker::Node;
+use crate::span::{Locator, Position, Span};
+use crate::special;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Policy {
+    /// Prefer the earliest alternative of every choice, like ordered choice.
+    #[default]
+    FirstAlternative,
+    /// Prefer the latest alternative of every choice.
+    LastAlternative,
+    /// Prefer derivations in which the trailing children are shortest, which
+    /// makes ambiguous binary operators group to the left.
+    LeftAssociative,
+    /// Prefer derivations in which the trailing children are longest, which
+    /// makes ambiguous binary operators group to the right.
+    RightAssociative,
+}
+
+/// A place in the input that more than one derivation accounts for.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Ambiguity {
+    /// The production rule the ambiguous expression belongs to.
+    pub rule: String,
+    /// The ambiguous stretch of input.
+    pub span: Span,
+    /// Number of distinct ways the input is derived at this point.
+    pub derivations: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Label {
+    Rule(String),
+    Group,
+    Intermediate,
+}
+
+#[derive(Debug, Clone)]
+enum Child {
+    Node(usize),
+    Terminal(String),
+    Special(Vec<Node>),
+}
+
+#[derive(Debug, Clone)]
+struct Family {
+    alternative: usize,
+    split: Position,
+    children: Vec<Child>,
+}
+
+#[derive(Debug, Clone)]
+struct ForestNode {
+    label: Label,
+    rule: String,
+    span: Span,
+    families: Vec<Family>,
+}
+
+/// A shared packed parse forest holding every derivation of an input.
+///
+/// Symbol nodes are shared between derivations and each node packs the
+/// alternative ways of deriving its stretch of input, so the forest stays
+/// polynomial in the length of the input even when the number of trees is
+/// exponential or infinite.
+#[derive(Debug, Clone)]
+pub struct Forest {
+    nodes: Vec<ForestNode>,
+    root: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum Key {
+    Symbol(usize, usize, usize),
+    Prefix(usize, usize, usize, usize),
+}
+
+struct Builder<'c, 'a> {
+    chart: &'c Chart<'a>,
+    locator: Locator<'a>,
+    nodes: Vec<ForestNode>,
+    keys: HashMap<Key, usize>,
+}
+
+impl<'c, 'a> Builder<'c, 'a> {
+    fn symbol(&mut self, nonterminal: usize, from: usize, to: usize) -> usize {
+        if let Some(&node) = self.keys.get(&Key::Symbol(nonterminal, from, to)) {
+            return node;
+        }
+        let bnf = self.chart.bnf;
+        let label = match &bnf.kinds[nonterminal] {
+            Kind::Named(name) => Label::Rule(name.clone()),
+            Kind::Auxiliary | Kind::Exception(_) => Label::Group,
+        };
+        let node = self.node(Key::Symbol(nonterminal, from, to), label, bnf.owners[nonterminal], from, to);
+        let mut families = Vec::new();
+        for (alternative, &rule) in bnf.alternatives[nonterminal].iter().enumerate() {
+            let length = bnf.rules[rule].rhs.len();
+            if length == 0 {
+                if from == to {
+                    families.push(Family {
+                        alternative,
+                        split: self.locator.position(from),
+                        children: Vec::new(),
+                    });
+                }
+            } else if self.chart.seen[to].contains(&Item { rule, dot: length, origin: from }) {
+                families.extend(self.splits(rule, length, from, to, alternative));
+            }
+        }
+        self.nodes[node].families = families;
+        node
+    }
+
+    fn prefix(&mut self, rule: usize, dot: usize, from: usize, to: usize) -> usize {
+        if let Some(&node) = self.keys.get(&Key::Prefix(rule, dot, from, to)) {
+            return node;
+        }
+        let owner = self.chart.bnf.owners[self.chart.bnf.rules[rule].lhs];
+        let node = self.node(Key::Prefix(rule, dot, from, to), Label::Intermediate, owner, from, to);
+        self.nodes[node].families = self.splits(rule, dot, from, to, 0);
+        node
+    }
+
+    fn node(&mut self, key: Key, label: Label, owner: usize, from: usize, to: usize) -> usize {
+        let rule = match &self.chart.bnf.kinds[owner] {
+            Kind::Named(name) => name.clone(),
+            _ => unreachable!(),
+        };
+        let span = self.locator.span(from, to);
+        self.nodes.push(ForestNode {
+            label,
+            rule,
+            span,
+            families: Vec::new(),
+        });
+        self.keys.insert(key, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// Every way of deriving the first `dot` symbols of `rule` between `from`
+    /// and `to`, split before the last of those symbols.
+    fn splits(&mut self, rule: usize, dot: usize, from: usize, to: usize, alternative: usize) -> Vec<Family> {
+        let chart = self.chart;
+        let mut families = Vec::new();
+        let middles = match &chart.bnf.rules[rule].rhs[dot - 1] {
+            Symbol::Nonterminal(nonterminal) => (from..=to)
+                .filter(|&middle| chart.spans(*nonterminal, middle, to))
+                .collect(),
+            Symbol::Terminal(content) => {
+                if to >= from + content.len() && chart.input[..to].ends_with(content.as_str()) {
+                    vec![to - content.len()]
+                } else {
+                    Vec::new()
+                }
+            }
+            Symbol::Special(content) => (from..=to)
+                .filter(|&middle| {
+                    chart.input.is_char_boundary(middle)
+                        && matches!(chart.specials.matches(content, &chart.input[middle..]), Some((len, _)) if len == to - middle)
+                })
+                .collect(),
+        };
+        for middle in middles {
+            let mut children = Vec::new();
+            if dot > 1 {
+                if !chart.seen[middle].contains(&Item { rule, dot: dot - 1, origin: from }) {
+                    continue;
+                }
+                children.push(Child::Node(self.prefix(rule, dot - 1, from, middle)));
+            } else if middle != from {
+                continue;
+            }
+            children.push(match &chart.bnf.rules[rule].rhs[dot - 1] {
+                Symbol::Nonterminal(nonterminal) => Child::Node(self.symbol(*nonterminal, middle, to)),
+                Symbol::Terminal(content) => Child::Terminal(content.clone()),
+                Symbol::Special(content) => {
+                    let matched = chart.specials.matches(content, &chart.input[middle..]).unwrap();
+                    Child::Special(special::matched_nodes(chart.input, &self.locator, middle, matched).unwrap_or_default())
+                }
+            });
+            families.push(Family {
+                alternative,
+                split: self.locator.position(middle),
+                children,
+            });
+        }
+        families
+    }
+}
+
+impl Forest {
+    pub(super) fn new(chart: &Chart, start: usize) -> Forest {
+        let mut builder = Builder {
+            chart,
+            locator: Locator::new(chart.input),
+            nodes: Vec::new(),
+            keys: HashMap::new(),
+        };
+        let root = builder.symbol(start, 0, chart.input.len());
+        Forest {
+            nodes: builder.nodes,
+            root,
+        }
+    }
+
+    /// Number of distinct derivations, saturating at `u128::MAX`, or `None`
+    /// if a rule derives itself and there are infinitely many.
+    pub fn count(&self) -> Option<u128> {
+        let mut counts = vec![None; self.nodes.len()];
+        let mut visiting = vec![false; self.nodes.len()];
+        self.count_node(self.root, &mut counts, &mut visiting)
+    }
+
+    fn count_node(&self, node: usize, counts: &mut Vec<Option<u128>>, visiting: &mut Vec<bool>) -> Option<u128> {
+        if let Some(count) = counts[node] {
+            return Some(count);
+        }
+        if visiting[node] {
+            return None;
+        }
+        visiting[node] = true;
+        let mut total: u128 = 0;
+        for family in self.nodes[node].families.iter() {
+            let mut product: u128 = 1;
+            for child in family.children.iter() {
+                if let Child::Node(child) = child {
+                    product = product.saturating_mul(self.count_node(*child, counts, visiting)?);
+                }
+            }
+            total = total.saturating_add(product);
+        }
+        visiting[node] = false;
+        counts[node] = Some(total);
+        Some(total)
+    }
+
+    /// Every place in the input where the forest packs more than one
+    /// derivation, ordered by position.
+    pub fn ambiguities(&self) -> Vec<Ambiguity> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut stack = vec![self.root];
+        while let Some(node) = stack.pop() {
+            if reachable[node] {
+                continue;
+            }
+            reachable[node] = true;
+            for family in self.nodes[node].families.iter() {
+                for child in family.children.iter() {
+                    if let Child::Node(child) = child {
+                        stack.push(*child);
+                    }
+                }
+            }
+        }
+        let mut ambiguities = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(index, node)| reachable[*index] && node.families.len() > 1)
+            .map(|(_, node)| Ambiguity {
+                rule: node.rule.clone(),
+                span: node.span,
+                derivations: node.families.len(),
+            })
+            .collect::<Vec<_>>();
+        ambiguities.sort_by_key(|ambiguity| (ambiguity.span.from.offset, ambiguity.span.to.offset));
+        ambiguities
+    }
+
+    /// Lazily enumerates every derivation in which no rule derives itself
+    /// over the same stretch of input, in declaration order of alternatives.
+    pub fn trees(&self) -> Trees<'_> {
+        self.trees_by(Policy::FirstAlternative)
+    }
+
+    /// The most preferred derivation according to `policy`.
+    pub fn disambiguate(&self, policy: Policy) -> Option<Node> {
+        self.trees_by(policy).next()
+    }
+
+    fn trees_by(&self, policy: Policy) -> Trees<'_> {
+        Trees {
+            forest: self,
+            policy,
+            choices: Vec::new(),
+            done: false,
+        }
+    }
+
+    fn order(&self, node: usize, policy: Policy) -> Vec<usize> {
+        let families = &self.nodes[node].families;
+        let mut order = (0..families.len()).collect::<Vec<_>>();
+        match policy {
+            Policy::FirstAlternative => {
+                order.sort_by_key(|&family| families[family].alternative)
+            }
+            Policy::LastAlternative => {
+                order.sort_by_key(|&family| std::cmp::Reverse(families[family].alternative))
+            }
+            Policy::LeftAssociative => {
+                order.sort_by_key(|&family| std::cmp::Reverse(families[family].split.offset))
+            }
+            Policy::RightAssociative => order.sort_by_key(|&family| families[family].split.offset),
+        }
+        order
+    }
+}
+
+pub struct Trees<'f> {
+    forest: &'f Forest,
+    policy: Policy,
+    choices: Vec<usize>,
+    done: bool,
+}
+
+impl<'f> Trees<'f> {
+    /// Builds the derivation selected by `choices`, visiting every forest node
+    /// as a choice point in depth-first order. On a dead end, returns the
+    /// index of the choice point that has no acyclic family left.
+    fn build(&mut self, node: usize, path: &mut Vec<usize>, cursor: &mut usize) -> Result<Vec<Node>, usize> {
+        let point = *cursor;
+        *cursor += 1;
+        if point == self.choices.len() {
+            self.choices.push(0);
+        }
+        let forest = self.forest;
+        let order = forest.order(node, self.policy);
+        let families = &forest.nodes[node].families;
+        let position = order.iter().skip(self.choices[point]).position(|&family| {
+            families[family].children.iter().all(|child| match child {
+                Child::Node(child) => !path.contains(child),
+                Child::Terminal(_) | Child::Special(_) => true,
+            })
+        });
+        let family = match position {
+            Some(position) => {
+                self.choices[point] += position;
+                &families[order[self.choices[point]]]
+            }
+            None => return Err(point),
+        };
+        path.push(node);
+        let mut nodes = Vec::new();
+        for child in family.children.iter() {
+            match child {
+                Child::Node(child) => nodes.extend(self.build(*child, path, cursor)?),
+                Child::Special(matched) => nodes.extend(matched.iter().cloned()),
+                Child::Terminal(content) => {
+                    let span = Span {
+                        from: family.split,
+                        to: forest.nodes[node].span.to,
+                    };
+                    nodes.push(Node::Terminal(content.clone(), span))
+                }
+            }
+        }
+        path.pop();
+        match &forest.nodes[node].label {
+            Label::Rule(name) => Ok(vec![Node::Nonterminal(name.clone(), nodes, forest.nodes[node].span)]),
+            Label::Group | Label::Intermediate => Ok(nodes),
+        }
+    }
+
+    fn backtrack(&mut self, point: usize) {
+        self.choices.truncate(point + 1);
+        self.choices[point] += 1;
+    }
+}
+
+impl<'f> Iterator for Trees<'f> {
+    type Item = Node;
+
+    fn next(&mut self) -> Option<Node> {
+        while !self.done {
+            match self.build(self.forest.root, &mut Vec::new(), &mut 0) {
+                Ok(mut nodes) => {
+                    let last = self.choices.len() - 1;
+                    self.backtrack(last);
+                    return Some(nodes.swap_remove(0));
+                }
+                Err(0) => self.done = true,
+                Err(point) => {
+                    self.choices.truncate(point);
+                    self.backtrack(point - 1);
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,211 @@
+mod bnf;
+pub mod forest;
+#[cfg(test)]
+mod tests;
+
+use super::ast::Grammar;
+use super::checker::{CheckError, Expected, Node, Options};
+use super::special::SpecialRegistry;
+use bnf::{Bnf, Kind, Symbol};
+pub use forest::{Ambiguity, Forest, Policy, Trees};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+struct Item {
+    rule: usize,
+    dot: usize,
+    origin: usize,
+}
+
+struct Chart<'a> {
+    input: &'a str,
+    bnf: &'a Bnf,
+    specials: &'a SpecialRegistry,
+    sets: Vec<Vec<Item>>,
+    seen: Vec<HashSet<Item>>,
+    completed: HashMap<(usize, usize), Vec<usize>>,
+    restrictions: HashMap<(usize, usize, usize), bool>,
+}
+
+impl<'a> Chart<'a> {
+    fn recognize(input: &'a str, bnf: &'a Bnf, specials: &'a SpecialRegistry, start: usize) -> Chart<'a> {
+        let mut chart = Chart {
+            input,
+            bnf,
+            specials,
+            sets: vec![Vec::new(); input.len() + 1],
+            seen: vec![HashSet::new(); input.len() + 1],
+            completed: HashMap::new(),
+            restrictions: HashMap::new(),
+        };
+        for &rule in bnf.alternatives[start].iter() {
+            chart.add(0, Item { rule, dot: 0, origin: 0 });
+        }
+        for position in 0..=input.len() {
+            chart.process(position);
+        }
+        chart
+    }
+
+    fn add(&mut self, position: usize, item: Item) {
+        if self.seen[position].insert(item) {
+            self.sets[position].push(item);
+        }
+    }
+
+    fn advance(item: Item) -> Item {
+        Item {
+            dot: item.dot + 1,
+            ..item
+        }
+    }
+
+    fn process(&mut self, position: usize) {
+        let mut index = 0;
+        while index < self.sets[position].len() {
+            let item = self.sets[position][index];
+            index += 1;
+            let bnf = self.bnf;
+            let rule = &bnf.rules[item.rule];
+            match rule.rhs.get(item.dot) {
+                Some(Symbol::Nonterminal(nonterminal)) => {
+                    let nonterminal = *nonterminal;
+                    for &rule in bnf.alternatives[nonterminal].iter() {
+                        self.add(position, Item { rule, dot: 0, origin: position });
+                    }
+                    if bnf.nullable[nonterminal] || self.spans(nonterminal, position, position) {
+                        self.add(position, Chart::advance(item));
+                    }
+                }
+                Some(Symbol::Terminal(content)) => {
+                    if self.input[position..].starts_with(content.as_str()) {
+                        self.add(position + content.len(), Chart::advance(item));
+                    }
+                }
+                Some(Symbol::Special(content)) => {
+                    if let Some((len, _)) = self.specials.matches(content, &self.input[position..]) {
+                        self.add(position + len, Chart::advance(item));
+                    }
+                }
+                None => self.complete(item, rule.lhs, position),
+            }
+        }
+    }
+
+    fn complete(&mut self, item: Item, lhs: usize, position: usize) {
+        if let Kind::Exception(restriction) = self.bnf.kinds[lhs] {
+            if self.derives(restriction, item.origin, position) {
+                return;
+            }
+        }
+        let ends = self.completed.entry((lhs, item.origin)).or_default();
+        if ends.contains(&position) {
+            return;
+        }
+        ends.push(position);
+        let mut index = 0;
+        while index < self.sets[item.origin].len() {
+            let waiting = self.sets[item.origin][index];
+            index += 1;
+            if self.bnf.rules[waiting.rule].rhs.get(waiting.dot) == Some(&Symbol::Nonterminal(lhs)) {
+                self.add(position, Chart::advance(waiting));
+            }
+        }
+    }
+
+    /// Whether the restriction of an exception derives exactly the input
+    /// between `from` and `to`.
+    fn derives(&mut self, restriction: usize, from: usize, to: usize) -> bool {
+        if let Some(result) = self.restrictions.get(&(restriction, from, to)) {
+            return *result;
+        }
+        let input = &self.input[from..to];
+        let result = Chart::recognize(input, self.bnf, self.specials, restriction).spans(restriction, 0, input.len());
+        self.restrictions.insert((restriction, from, to), result);
+        result
+    }
+
+    fn spans(&self, nonterminal: usize, from: usize, to: usize) -> bool {
+        match self.completed.get(&(nonterminal, from)) {
+            Some(ends) => ends.contains(&to),
+            None => false,
+        }
+    }
+
+    fn error(&self, start: usize) -> CheckError {
+        let position = (0..self.sets.len())
+            .rev()
+            .find(|&position| !self.sets[position].is_empty())
+            .unwrap_or(0);
+        let mut expected = Vec::new();
+        for item in self.sets[position].iter() {
+            let symbol = match self.bnf.rules[item.rule].rhs.get(item.dot) {
+                Some(Symbol::Terminal(content)) => Expected::Terminal(content.clone()),
+                Some(Symbol::Special(content)) => Expected::Special(content.clone()),
+                _ => continue,
+            };
+            if !expected.contains(&symbol) {
+                expected.push(symbol);
+            }
+        }
+        if self.spans(start, 0, position) {
+            expected.push(Expected::EndOfInput);
+        }
+        CheckError {
+            position,
+            expected,
+            stack: Vec::new(),
+        }
+    }
+}
+
+/// Checks the input using true context-free recognition, returning a forest
+/// of every derivation of the initial rule.
+pub fn parse_forest(input: &str, grammar: &Grammar, initial_rule: &str) -> Result<Forest, CheckError> {
+    parse_forest_with(input, grammar, initial_rule, &Options::default())
+}
+
+/// Like `parse_forest`, matching special sequences with the registry in
+/// `options`. The other options do not apply to Earley recognition.
+pub fn parse_forest_with(
+    input: &str,
+    grammar: &Grammar,
+    initial_rule: &str,
+    options: &Options,
+) -> Result<Forest, CheckError> {
+    let bnf = Bnf::new(grammar);
+    match bnf.named(initial_rule) {
+        Some(start) => {
+            let chart = Chart::recognize(input, &bnf, &options.specials, start);
+            if chart.spans(start, 0, input.len()) {
+                Ok(Forest::new(&chart, start))
+            } else {
+                Err(chart.error(start))
+            }
+        }
+        None => Err(CheckError {
+            position: 0,
+            expected: vec![Expected::Rule(initial_rule.to_owned())],
+            stack: Vec::new(),
+        }),
+    }
+}
+
+pub(crate) fn check(input: &str, grammar: &Grammar, initial_rule: &str, options: &Options) -> Result<Node, CheckError> {
+    let forest = parse_forest_with(input, grammar, initial_rule, options)?;
+    match forest.trees().next() {
+        Some(node) => Ok(node),
+        None => Err(CheckError {
+            position: input.len(),
+            expected: Vec::new(),
+            stack: Vec::new(),
+        }),
+    }
+}
+
+/// Checks the input using true context-free recognition, returning every
+/// derivation of the initial rule in which no rule derives itself over the
+/// same stretch of input.
+pub fn check_all(input: &str, grammar: &Grammar, initial_rule: &str) -> Result<Vec<Node>, CheckError> {
+    Ok(parse_forest(input, grammar, initial_rule)?.trees().collect())
+}
@@ -0,0 +1,282 @@
+use super::{check_all, parse_forest, Ambiguity, Policy};
+use crate::ast::{Expression, Grammar};
+use crate::builder::{alternative, nonterminal, sequence, terminal};
+use crate::checker::{check, check_with, CheckError, Engine, Expected, Node, Options};
+use crate::span::{Position, Span};
+use crate::special::SpecialRegistry;
+use crate::testing::{branch, leaf, strip, strip_all};
+
+const EARLEY: Options = Options {
+    engine: Engine::Earley,
+    packrat: false,
+    memo_capacity: None,
+    specials: SpecialRegistry::new(),
+};
+
+#[test]
+fn test_ordered_choice_is_not_committed() {
+    // a = 'x' | 'x', 'y';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "a".to_owned(),
+        alternative(vec![terminal("x"), sequence(vec![terminal("x"), terminal("y")])]),
+    );
+    assert!(check("xy", &grammar, "a").is_err());
+    assert_eq!(
+        check_with("xy", &grammar, "a", &EARLEY).map(strip),
+        Ok(branch("a", vec![leaf("x"), leaf("y")]))
+    );
+}
+
+#[test]
+fn test_left_recursion() {
+    // e = e, '+', 'n' | 'n';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "e".to_owned(),
+        alternative(vec![
+            sequence(vec![nonterminal("e"), terminal("+"), terminal("n")]),
+            terminal("n"),
+        ]),
+    );
+    assert_eq!(
+        check_all("n+n+n", &grammar, "e").map(strip_all),
+        Ok(vec![branch(
+            "e",
+            vec![
+                branch("e", vec![branch("e", vec![leaf("n")]), leaf("+"), leaf("n")]),
+                leaf("+"),
+                leaf("n"),
+            ]
+        )])
+    );
+}
+
+#[test]
+fn test_every_derivation() {
+    let grammar = ambiguous_grammar();
+    let n = || branch("e", vec![leaf("n")]);
+    let derivations = strip_all(check_all("n+n+n", &grammar, "e").unwrap());
+    assert_eq!(derivations.len(), 2);
+    assert!(derivations.contains(&branch(
+        "e",
+        vec![branch("e", vec![n(), leaf("+"), n()]), leaf("+"), n()]
+    )));
+    assert!(derivations.contains(&branch(
+        "e",
+        vec![n(), leaf("+"), branch("e", vec![n(), leaf("+"), n()])]
+    )));
+}
+
+// e = e, '+', e | 'n';
+fn ambiguous_grammar() -> Grammar {
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "e".to_owned(),
+        alternative(vec![
+            sequence(vec![nonterminal("e"), terminal("+"), nonterminal("e")]),
+            terminal("n"),
+        ]),
+    );
+    grammar
+}
+
+#[test]
+fn test_forest_count() {
+    let grammar = ambiguous_grammar();
+    let counts = ["n", "n+n", "n+n+n", "n+n+n+n", "n+n+n+n+n"]
+        .iter()
+        .map(|input| parse_forest(input, &grammar, "e").unwrap().count())
+        .collect::<Vec<_>>();
+    assert_eq!(counts, vec![Some(1), Some(1), Some(2), Some(5), Some(14)]);
+    let forest = parse_forest("n+n+n+n+n", &grammar, "e").unwrap();
+    assert_eq!(forest.trees().count(), 14);
+    let long = vec!["n"; 40].join("+");
+    assert_eq!(
+        parse_forest(&long, &grammar, "e").unwrap().count(),
+        Some(680_425_371_729_975_800_390)
+    );
+}
+
+#[test]
+fn test_forest_cycle() {
+    // a = a | 'x';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "a".to_owned(),
+        alternative(vec![nonterminal("a"), terminal("x")]),
+    );
+    let forest = parse_forest("x", &grammar, "a").unwrap();
+    assert_eq!(forest.count(), None);
+    assert_eq!(
+        strip_all(forest.trees().collect()),
+        vec![branch("a", vec![leaf("x")])]
+    );
+}
+
+#[test]
+fn test_forest_disambiguation() {
+    let grammar = ambiguous_grammar();
+    let n = || branch("e", vec![leaf("n")]);
+    let forest = parse_forest("n+n+n", &grammar, "e").unwrap();
+    assert_eq!(
+        forest.disambiguate(Policy::LeftAssociative).map(strip),
+        Some(branch(
+            "e",
+            vec![branch("e", vec![n(), leaf("+"), n()]), leaf("+"), n()]
+        ))
+    );
+    assert_eq!(
+        forest.disambiguate(Policy::RightAssociative).map(strip),
+        Some(branch(
+            "e",
+            vec![n(), leaf("+"), branch("e", vec![n(), leaf("+"), n()])]
+        ))
+    );
+    assert_eq!(
+        forest.ambiguities(),
+        vec![Ambiguity {
+            rule: "e".to_owned(),
+            span: Span {
+                from: Position::default(),
+                to: Position {
+                    offset: 5,
+                    char: 5,
+                    line: 0,
+                    column: 5,
+                },
+            },
+            derivations: 2,
+        }]
+    );
+    assert_eq!(parse_forest("n+n", &grammar, "e").unwrap().ambiguities(), vec![]);
+}
+
+#[test]
+fn test_forest_spans() {
+    let grammar = ambiguous_grammar();
+    let span = |from, to| Span {
+        from: Position {
+            offset: from,
+            char: from,
+            line: 0,
+            column: from,
+        },
+        to: Position {
+            offset: to,
+            char: to,
+            line: 0,
+            column: to,
+        },
+    };
+    let node = check_all("n+n", &grammar, "e").unwrap().remove(0);
+    assert_eq!(
+        node,
+        Node::Nonterminal(
+            "e".to_owned(),
+            vec![
+                Node::Nonterminal(
+                    "e".to_owned(),
+                    vec![Node::Terminal("n".to_owned(), span(0, 1))],
+                    span(0, 1)
+                ),
+                Node::Terminal("+".to_owned(), span(1, 2)),
+                Node::Nonterminal(
+                    "e".to_owned(),
+                    vec![Node::Terminal("n".to_owned(), span(2, 3))],
+                    span(2, 3)
+                ),
+            ],
+            span(0, 3)
+        )
+    );
+}
+
+#[test]
+fn test_repetition_and_exception() {
+    // w = { 'a' } - 'aa';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "w".to_owned(),
+        Expression::Exception {
+            subject: Box::new(Expression::Repeated(Box::new(terminal("a")))),
+            restriction: Box::new(terminal("aa")),
+        },
+    );
+    assert_eq!(check_all("", &grammar, "w").map(strip_all), Ok(vec![branch("w", vec![])]));
+    assert_eq!(
+        check_all("aaa", &grammar, "w").map(strip_all),
+        Ok(vec![branch("w", vec![leaf("a"), leaf("a"), leaf("a")])])
+    );
+    assert!(check_all("aa", &grammar, "w").is_err());
+}
+
+#[test]
+fn test_exception_of_empty_input() {
+    // s = a, 'y';
+    // a = [ 'x' ] - ? blank ?;
+    let mut grammar = Grammar::new();
+    grammar.insert("s".to_owned(), sequence(vec![nonterminal("a"), terminal("y")]));
+    grammar.insert(
+        "a".to_owned(),
+        Expression::Exception {
+            subject: Box::new(Expression::Optional(Box::new(terminal("x")))),
+            restriction: Box::new(Expression::Special("blank".to_owned())),
+        },
+    );
+    let mut options = EARLEY;
+    options.specials.insert("blank", |_| Some((0, None)));
+    assert!(check_with("xy", &grammar, "s", &options).is_ok());
+    assert_eq!(check_with("y", &grammar, "s", &options).map_err(|e| e.position), Err(0));
+}
+
+#[test]
+fn test_error() {
+    // e = e, '+', 'n' | 'n';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "e".to_owned(),
+        alternative(vec![
+            sequence(vec![nonterminal("e"), terminal("+"), terminal("n")]),
+            terminal("n"),
+        ]),
+    );
+    assert_eq!(
+        check_all("n+n+", &grammar, "e"),
+        Err(CheckError {
+            position: 4,
+            expected: vec![Expected::Terminal("n".to_owned())],
+            stack: vec![],
+        })
+    );
+    assert_eq!(
+        check_all("n+nn", &grammar, "e"),
+        Err(CheckError {
+            position: 3,
+            expected: vec![Expected::Terminal("+".to_owned()), Expected::EndOfInput],
+            stack: vec![],
+        })
+    );
+}
+
+#[test]
+fn test_builtin_special_sequences() {
+    // s = { ? any character ? - 'x' }, 'x', ? end of input ?;
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "s".to_owned(),
+        sequence(vec![
+            Expression::Repeated(Box::new(Expression::Exception {
+                subject: Box::new(Expression::Special("any character".to_owned())),
+                restriction: Box::new(terminal("x")),
+            })),
+            terminal("x"),
+            Expression::Special("end of input".to_owned()),
+        ]),
+    );
+    assert_eq!(
+        check_all("aéx", &grammar, "s").map(strip_all),
+        Ok(vec![branch("s", vec![leaf("a"), leaf("é"), leaf("x")])])
+    );
+    assert!(check_all("axx", &grammar, "s").is_err());
+}
@@ -1,8 +1,31 @@
 #![feature(box_syntax, box_patterns, bindings_after_at)]
 
+mod analysis;
 pub mod ast;
+pub mod binary;
+pub mod builder;
 pub mod checker;
+pub mod earley;
+pub mod printer;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod span;
+pub mod special;
+#[cfg(test)]
+mod testing;
+pub mod transform;
+pub mod visit;
 
-pub use ast::{Grammar, Expression};
+pub use analysis::left_recursion;
+pub use ast::{Grammar, Expression, Rule};
+pub use builder::{BuildError, Builder};
 pub use checker::Node;
-pub use checker::check;
+pub use checker::{check, check_with, CheckError, Engine, Expected, Options};
+pub use earley::{check_all, parse_forest, parse_forest_with, Forest, Policy};
+pub use printer::to_ebnf;
+pub use special::SpecialRegistry;
+pub use span::{Position, Span};
+pub use transform::{
+    eliminate_left_recursion, eliminate_left_recursion_with, Eliminated, EliminationError,
+};
+pub use visit::{Fold, Visitor, VisitorMut};
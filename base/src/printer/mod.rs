@@ -0,0 +1,203 @@
+//! Printing of grammars as ISO 14977 EBNF. The layout helpers are shared
+//! with the formatter of `ebnf`, so that both print expressions alike.
+
+use super::ast::{Expression, Grammar};
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+/// How tightly an expression binds, loosest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    Alternative,
+    Sequence,
+    Exception,
+    Factor,
+    Primary,
+}
+
+/// Splits the content of a terminal into pieces that can each be quoted,
+/// since no terminal can contain both kinds of quotes.
+fn quotable(content: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    let (mut single, mut double) = (false, false);
+    for (i, ch) in content.char_indices() {
+        single |= ch == '\'';
+        double |= ch == '"';
+        if single && double {
+            pieces.push(&content[start..i]);
+            start = i;
+            single = ch == '\'';
+            double = ch == '"';
+        }
+    }
+    pieces.push(&content[start..]);
+    pieces
+}
+
+fn precedence(expression: &Expression) -> Precedence {
+    match expression {
+        Expression::Alternative { .. } => Precedence::Alternative,
+        Expression::Sequence { .. } => Precedence::Sequence,
+        Expression::Terminal(content) if quotable(content).len() > 1 => Precedence::Sequence,
+        Expression::Exception { .. } => Precedence::Exception,
+        Expression::Factor { .. } => Precedence::Factor,
+        _ => Precedence::Primary,
+    }
+}
+
+/// A printed operand, in parentheses if it binds looser than `loosest`.
+pub fn group(printed: String, precedence: Precedence, loosest: Precedence) -> String {
+    if precedence < loosest {
+        format!("({}", terminate(&printed, ")"))
+    } else {
+        printed
+    }
+}
+
+fn operand(expression: &Expression, loosest: Precedence) -> String {
+    group(expression.to_string(), precedence(expression), loosest)
+}
+
+/// Joins printed operands with a symbol, with `before` ahead of it, leaving
+/// out the spaces around empty ones.
+pub fn join<I>(operands: I, before: &str, symbol: &str) -> String
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut operands = operands.into_iter();
+    let mut joined = operands.next().unwrap_or_default();
+    for operand in operands {
+        if !joined.is_empty() {
+            joined.push_str(before);
+        }
+        joined.push_str(symbol);
+        if !operand.is_empty() {
+            joined.push(' ');
+            joined.push_str(&operand);
+        }
+    }
+    joined
+}
+
+/// Appends `terminator` to `text`, with a space between them when `text`
+/// ends with an operator or `=`, so that the empty operand after it shows,
+/// as in `a = 'x', ;`.
+pub fn terminate(text: &str, terminator: &str) -> String {
+    if text.ends_with(&[',', '|', '*', '-', '='][..]) {
+        format!("{} {}", text, terminator)
+    } else {
+        format!("{}{}", text, terminator)
+    }
+}
+
+/// A printed expression between a pair of brackets, which are written
+/// together when it's empty.
+pub fn enclose(open: &str, inner: &str, close: &str) -> String {
+    if inner.is_empty() {
+        format!("{}{}", open, close)
+    } else {
+        format!("{} {} {}", open, inner, close)
+    }
+}
+
+impl fmt::Display for Expression {
+    /// Writes the expression as ISO 14977 EBNF, with only the parentheses
+    /// needed to keep its structure. Nested alternatives and sequences are
+    /// parenthesized too, so that they read back as the same tree.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let printed = match self {
+            Expression::Alternative {
+                first: box first,
+                second: box second,
+                rest,
+            } => join(
+                [first, second]
+                    .iter()
+                    .copied()
+                    .chain(rest.iter())
+                    .map(|expression| operand(expression, Precedence::Sequence)),
+                " ",
+                "|",
+            ),
+            Expression::Sequence {
+                first: box first,
+                second: box second,
+                rest,
+            } => join(
+                [first, second]
+                    .iter()
+                    .copied()
+                    .chain(rest.iter())
+                    .map(|expression| operand(expression, Precedence::Exception)),
+                "",
+                ",",
+            ),
+            Expression::Optional(box inner) => enclose("[", &inner.to_string(), "]"),
+            Expression::Repeated(box inner) => enclose("{", &inner.to_string(), "}"),
+            Expression::Factor {
+                count,
+                primary: box primary,
+            } => join(
+                vec![count.to_string(), operand(primary, Precedence::Primary)],
+                " ",
+                "*",
+            ),
+            Expression::Exception {
+                subject: box subject,
+                restriction: box restriction,
+            } => join(
+                vec![
+                    operand(subject, Precedence::Factor),
+                    operand(restriction, Precedence::Factor),
+                ],
+                " ",
+                "-",
+            ),
+            Expression::Nonterminal(identifier) => identifier.clone(),
+            Expression::Terminal(content) if content.is_empty() => String::new(),
+            Expression::Terminal(content) => join(
+                quotable(content)
+                    .into_iter()
+                    .map(|piece| match piece.contains('\'') {
+                        true => format!("\"{}\"", piece),
+                        false => format!("'{}'", piece),
+                    }),
+                "",
+                ",",
+            ),
+            Expression::Special(content) => format!("?{}?", content),
+            Expression::Empty => String::new(),
+        };
+        write!(f, "{}", printed)
+    }
+}
+
+impl fmt::Display for Grammar {
+    /// Writes the grammar as ISO 14977 EBNF, one production per line in the
+    /// order they were declared in, each under its doc comment.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for rule in self.rules() {
+            for line in rule.doc.iter().flat_map(|doc| doc.lines()) {
+                writeln!(f, "(* {} *)", line)?;
+            }
+            let mut line = format!("{} =", rule.name);
+            match rule.expression.to_string() {
+                body if body.is_empty() => {}
+                body => line = format!("{} {}", line, body),
+            }
+            writeln!(f, "{}", terminate(&line, ";"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes a grammar as ISO 14977 EBNF, such that `ebnf::parse` reads it back
+/// as the same grammar. Rule names and special sequences are written as they
+/// are, so they must be ones the lexer accepts. A start rule other than the
+/// first one is lost.
+pub fn to_ebnf(grammar: &Grammar) -> String {
+    grammar.to_string()
+}
@@ -0,0 +1,81 @@
+use super::to_ebnf;
+use crate::ast::{Grammar, Rule};
+use crate::builder::{empty, nonterminal, special, terminal};
+use crate::{alt, seq};
+
+#[test]
+fn test_primaries() {
+    assert_eq!(terminal("a").to_string(), "'a'");
+    assert_eq!(terminal("a'b").to_string(), "\"a'b\"");
+    assert_eq!(terminal("'\"").to_string(), "\"'\", '\"'");
+    assert_eq!(terminal("").to_string(), "");
+    assert_eq!(nonterminal("rule name").to_string(), "rule name");
+    assert_eq!(special("x").to_string(), "?x?");
+    assert_eq!(empty().to_string(), "");
+    assert_eq!(empty().times(2).to_string(), "2 *");
+    assert_eq!(empty().except(terminal("a")).to_string(), "- 'a'");
+    assert_eq!(empty().rep().to_string(), "{}");
+    assert_eq!(empty().opt().to_string(), "[]");
+}
+
+#[test]
+fn test_parenthesization() {
+    assert_eq!(
+        seq![seq![terminal("a"), terminal("b")], terminal("c")].to_string(),
+        "('a', 'b'), 'c'"
+    );
+    assert_eq!(
+        alt![terminal("a"), alt![terminal("b"), terminal("c")]].to_string(),
+        "'a' | ('b' | 'c')"
+    );
+    assert_eq!(
+        alt![seq![terminal("a"), terminal("b")], terminal("c")].to_string(),
+        "'a', 'b' | 'c'"
+    );
+    assert_eq!(
+        seq![alt![terminal("a"), terminal("b")], terminal("c")].to_string(),
+        "('a' | 'b'), 'c'"
+    );
+    assert_eq!(
+        seq![nonterminal("a").except(terminal("b")), terminal("c")].to_string(),
+        "a - 'b', 'c'"
+    );
+    assert_eq!(
+        seq![nonterminal("a"), nonterminal("b")]
+            .except(terminal("c").times(2))
+            .to_string(),
+        "(a, b) - 2 * 'c'"
+    );
+    assert_eq!(
+        nonterminal("a")
+            .except(nonterminal("b").except(nonterminal("c")))
+            .to_string(),
+        "a - (b - c)"
+    );
+    assert_eq!(
+        alt![terminal("a"), terminal("b")].times(3).to_string(),
+        "3 * ('a' | 'b')"
+    );
+    assert_eq!(terminal("a").times(3).times(2).to_string(), "2 * (3 * 'a')");
+    assert_eq!(terminal("'\"").times(2).to_string(), "2 * (\"'\", '\"')");
+    assert_eq!(alt![terminal("a"), empty()].opt().to_string(), "[ 'a' | ]");
+    assert_eq!(
+        seq![terminal("a"), terminal("b")].rep().to_string(),
+        "{ 'a', 'b' }"
+    );
+}
+
+#[test]
+fn test_grammar() {
+    let mut grammar = Grammar::new();
+    grammar.insert("b".to_owned(), empty());
+    grammar.insert("a".to_owned(), alt![nonterminal("b"), terminal("x")]);
+    grammar.insert_rule(Rule {
+        doc: Some("first line\nsecond line".to_owned()),
+        ..Rule::new("c", nonterminal("a"))
+    });
+    assert_eq!(
+        to_ebnf(&grammar),
+        "b = ;\na = b | 'x';\n(* first line *)\n(* second line *)\nc = a;\n"
+    );
+}
@@ -0,0 +1,163 @@
+//! With the `serde` feature, grammars and parse trees can be serialized and
+//! deserialized. In JSON, they look as follows.
+//!
+//! A [`Grammar`](crate::Grammar) is an object with its rules in the order
+//! they were declared in, and the rule to check input from, which is `null`
+//! for a grammar without rules:
+//!
+//! ```json
+//! {
+//!   "start": "digits",
+//!   "rules": [
+//!     {
+//!       "name": "digits",
+//!       "expression": { "repeated": { "special": "digit" } },
+//!       "doc": "any number of digits",
+//!       "span": {
+//!         "from": { "offset": 0, "char": 0, "line": 0, "column": 0 },
+//!         "to": { "offset": 19, "char": 19, "line": 0, "column": 19 }
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `doc` and `span` are left out for rules that have none. Rule names have to
+//! be unique, and the start rule has to be one of them.
+//!
+//! An [`Expression`](crate::Expression) is an object with a single key
+//! naming its kind, except for the empty expression, which is the string
+//! `"empty"`:
+//!
+//! | EBNF          | JSON                                                          |
+//! |---------------|---------------------------------------------------------------|
+//! | `a \| b \| c` | `{ "alternative": { "first": a, "second": b, "rest": [c] } }` |
+//! | `a, b, c`     | `{ "sequence": { "first": a, "second": b, "rest": [c] } }`    |
+//! | `[ a ]`       | `{ "optional": a }`                                           |
+//! | `{ a }`       | `{ "repeated": a }`                                           |
+//! | `2 * a`       | `{ "factor": { "count": 2, "primary": a } }`                  |
+//! | `a - b`       | `{ "exception": { "subject": a, "restriction": b } }`         |
+//! | `a`           | `{ "nonterminal": "a" }`                                      |
+//! | `'a'`         | `{ "terminal": "a" }`                                         |
+//! | `?a?`         | `{ "special": "a" }`                                          |
+//! | (empty)       | `"empty"`                                                     |
+//!
+//! A [`Node`](crate::Node) of a parse tree is either a terminal with the
+//! text it matched, or a nonterminal with the rule it was checked with, each
+//! along with the span of the input it covers:
+//!
+//! ```text
+//! { "terminal": { "content": "1", "span": { "from": ..., "to": ... } } }
+//! { "nonterminal": { "name": "digits", "children": [...], "span": { "from": ..., "to": ... } } }
+//! ```
+
+use super::ast::{Grammar, Rule};
+use super::checker::Node;
+use super::span::Span;
+use serde::de::{self, Deserialize, Deserializer};
+use serde::ser::{Serialize, SerializeStruct, SerializeStructVariant, Serializer};
+
+#[cfg(test)]
+mod tests;
+
+impl Serialize for Grammar {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut grammar = serializer.serialize_struct("Grammar", 2)?;
+        grammar.serialize_field("start", &self.start())?;
+        grammar.serialize_field("rules", self.rules())?;
+        grammar.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "Grammar")]
+struct GrammarData {
+    #[serde(default)]
+    start: Option<String>,
+    rules: Vec<Rule>,
+}
+
+impl<'de> Deserialize<'de> for Grammar {
+    fn deserialize<D>(deserializer: D) -> Result<Grammar, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let GrammarData { start, rules } = GrammarData::deserialize(deserializer)?;
+        let mut grammar = Grammar::new();
+        for rule in rules {
+            let name = rule.name.clone();
+            if grammar.insert_rule(rule).is_some() {
+                return Err(de::Error::custom(format!(
+                    "rule {} is defined more than once",
+                    name
+                )));
+            }
+        }
+        if let Some(start) = start {
+            if !grammar.contains_key(&start) {
+                return Err(de::Error::custom(format!(
+                    "start rule {} is undefined",
+                    start
+                )));
+            }
+            grammar.set_start(&start);
+        }
+        Ok(grammar)
+    }
+}
+
+impl Serialize for Node {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Node::Nonterminal(name, children, span) => {
+                let mut node = serializer.serialize_struct_variant("Node", 0, "nonterminal", 3)?;
+                node.serialize_field("name", name)?;
+                node.serialize_field("children", children)?;
+                node.serialize_field("span", span)?;
+                node.end()
+            }
+            Node::Terminal(content, span) => {
+                let mut node = serializer.serialize_struct_variant("Node", 1, "terminal", 2)?;
+                node.serialize_field("content", content)?;
+                node.serialize_field("span", span)?;
+                node.end()
+            }
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "Node", rename_all = "snake_case")]
+enum NodeData {
+    Nonterminal {
+        name: String,
+        children: Vec<Node>,
+        span: Span,
+    },
+    Terminal {
+        content: String,
+        span: Span,
+    },
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D>(deserializer: D) -> Result<Node, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match NodeData::deserialize(deserializer)? {
+            NodeData::Nonterminal {
+                name,
+                children,
+                span,
+            } => Node::Nonterminal(name, children, span),
+            NodeData::Terminal { content, span } => Node::Terminal(content, span),
+        })
+    }
+}
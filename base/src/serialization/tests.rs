@@ -0,0 +1,140 @@
+use crate::ast::{Expression, Grammar, Rule};
+use crate::builder::{nonterminal, special, terminal};
+use crate::checker::{check, Node};
+use crate::span::{Position, Span};
+use crate::{alt, seq};
+use serde_json::json;
+
+fn position(offset: usize) -> Position {
+    Position {
+        offset,
+        char: offset,
+        line: 0,
+        column: offset,
+    }
+}
+
+fn span(from: usize, to: usize) -> Span {
+    Span {
+        from: position(from),
+        to: position(to),
+    }
+}
+
+// number = digit, { digit };
+// digit = ?digit? | '_';
+fn grammar() -> Grammar {
+    let mut grammar = Grammar::new();
+    grammar.insert_rule(Rule {
+        doc: Some("an unsigned integer".to_owned()),
+        span: Some(span(0, 26)),
+        ..Rule::new(
+            "number",
+            seq![nonterminal("digit"), nonterminal("digit").rep()],
+        )
+    });
+    grammar.insert(
+        "digit".to_owned(),
+        alt![special("digit"), terminal("_")]
+            .times(1)
+            .except(Expression::Empty),
+    );
+    grammar
+}
+
+#[test]
+fn test_grammar() {
+    let grammar = grammar();
+    let value = serde_json::to_value(&grammar).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "start": "number",
+            "rules": [
+                {
+                    "name": "number",
+                    "expression": {
+                        "sequence": {
+                            "first": { "nonterminal": "digit" },
+                            "second": { "repeated": { "nonterminal": "digit" } },
+                            "rest": [],
+                        },
+                    },
+                    "doc": "an unsigned integer",
+                    "span": {
+                        "from": { "offset": 0, "char": 0, "line": 0, "column": 0 },
+                        "to": { "offset": 26, "char": 26, "line": 0, "column": 26 },
+                    },
+                },
+                {
+                    "name": "digit",
+                    "expression": {
+                        "exception": {
+                            "subject": {
+                                "factor": {
+                                    "count": 1,
+                                    "primary": {
+                                        "alternative": {
+                                            "first": { "special": "digit" },
+                                            "second": { "terminal": "_" },
+                                            "rest": [],
+                                        },
+                                    },
+                                },
+                            },
+                            "restriction": "empty",
+                        },
+                    },
+                },
+            ],
+        })
+    );
+
+    let deserialized: Grammar = serde_json::from_value(value).unwrap();
+    assert_eq!(deserialized, grammar);
+    assert_eq!(deserialized.rules(), grammar.rules());
+}
+
+#[test]
+fn test_grammar_start() {
+    let mut grammar = grammar();
+    grammar.set_start("digit");
+    let json = serde_json::to_string(&grammar).unwrap();
+    let deserialized: Grammar = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.start(), Some("digit"));
+
+    let empty: Grammar = serde_json::from_str(r#"{ "start": null, "rules": [] }"#).unwrap();
+    assert!(empty.is_empty());
+    assert_eq!(empty.start(), None);
+}
+
+#[test]
+fn test_invalid_grammar() {
+    let rule = json!({ "name": "a", "expression": { "terminal": "a" } });
+    let error = serde_json::from_value::<Grammar>(json!({ "rules": [rule, rule] })).unwrap_err();
+    assert_eq!(error.to_string(), "rule a is defined more than once");
+    let error =
+        serde_json::from_value::<Grammar>(json!({ "start": "b", "rules": [rule] })).unwrap_err();
+    assert_eq!(error.to_string(), "start rule b is undefined");
+}
+
+#[test]
+fn test_node() {
+    let grammar = grammar();
+    let node = check("1_", &grammar, "number").unwrap();
+    let value = serde_json::to_value(&node).unwrap();
+    assert_eq!(value["nonterminal"]["name"], "number");
+    assert_eq!(
+        value["nonterminal"]["children"][1]["nonterminal"]["children"][0],
+        json!({
+            "terminal": {
+                "content": "_",
+                "span": {
+                    "from": { "offset": 1, "char": 1, "line": 0, "column": 1 },
+                    "to": { "offset": 2, "char": 2, "line": 0, "column": 2 },
+                },
+            },
+        })
+    );
+    assert_eq!(serde_json::from_value::<Node>(value).unwrap(), node);
+}
@@ -0,0 +1,80 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Position {
+    /// Offset from the start of the input in bytes.
+    pub offset: usize,
+    /// Offset from the start of the input in characters.
+    pub char: usize,
+    /// Zero-based line number.
+    pub line: usize,
+    /// Zero-based column in characters.
+    pub column: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub from: Position,
+    pub to: Position,
+}
+
+/// Bytes between the checkpoints a position's character offset is counted
+/// from.
+const STRIDE: usize = 64;
+
+/// Maps byte offsets into an input to full positions.
+pub(crate) struct Locator<'a> {
+    input: &'a str,
+    /// Byte and character offsets of the start of every line.
+    lines: Vec<(usize, usize)>,
+    /// Byte and character offsets of the first character after every
+    /// `STRIDE` bytes, so that counting characters takes constant time even
+    /// on long lines.
+    checkpoints: Vec<(usize, usize)>,
+}
+
+impl<'a> Locator<'a> {
+    pub fn new(input: &'a str) -> Locator<'a> {
+        let mut lines = vec![(0, 0)];
+        let mut checkpoints = vec![(0, 0)];
+        let mut chars = input.char_indices().enumerate().peekable();
+        while let Some((char, (offset, ch))) = chars.next() {
+            if offset >= checkpoints.len() * STRIDE {
+                checkpoints.push((offset, char));
+            }
+            let line_break = match ch {
+                '\n' => true,
+                '\r' => !matches!(chars.peek(), Some((_, (_, '\n')))),
+                _ => false,
+            };
+            if line_break {
+                lines.push((offset + ch.len_utf8(), char + 1));
+            }
+        }
+        Locator {
+            input,
+            lines,
+            checkpoints,
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let line = self.lines.partition_point(|&(start, _)| start <= offset) - 1;
+        let checkpoint = self.checkpoints.partition_point(|&(start, _)| start <= offset) - 1;
+        let (start, char) = self.checkpoints[checkpoint];
+        let char = char + self.input[start..offset].chars().count();
+        Position {
+            offset,
+            char,
+            line,
+            column: char - self.lines[line].1,
+        }
+    }
+
+    pub fn span(&self, from: usize, to: usize) -> Span {
+        Span {
+            from: self.position(from),
+            to: self.position(to),
+        }
+    }
+}
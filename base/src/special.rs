@@ -0,0 +1,227 @@
+use crate::checker::Node;
+use crate::span::{Locator, Span};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+use unicode_general_category::{get_general_category, GeneralCategory};
+
+const CATEGORIES: &[(&str, &str, GeneralCategory)] = &[
+    ("lu", "uppercase letter", GeneralCategory::UppercaseLetter),
+    ("ll", "lowercase letter", GeneralCategory::LowercaseLetter),
+    ("lt", "titlecase letter", GeneralCategory::TitlecaseLetter),
+    ("lm", "modifier letter", GeneralCategory::ModifierLetter),
+    ("lo", "other letter", GeneralCategory::OtherLetter),
+    ("mn", "nonspacing mark", GeneralCategory::NonspacingMark),
+    ("mc", "spacing mark", GeneralCategory::SpacingMark),
+    ("me", "enclosing mark", GeneralCategory::EnclosingMark),
+    ("nd", "decimal number", GeneralCategory::DecimalNumber),
+    ("nl", "letter number", GeneralCategory::LetterNumber),
+    ("no", "other number", GeneralCategory::OtherNumber),
+    ("pc", "connector punctuation", GeneralCategory::ConnectorPunctuation),
+    ("pd", "dash punctuation", GeneralCategory::DashPunctuation),
+    ("ps", "open punctuation", GeneralCategory::OpenPunctuation),
+    ("pe", "close punctuation", GeneralCategory::ClosePunctuation),
+    ("pi", "initial punctuation", GeneralCategory::InitialPunctuation),
+    ("pf", "final punctuation", GeneralCategory::FinalPunctuation),
+    ("po", "other punctuation", GeneralCategory::OtherPunctuation),
+    ("sm", "math symbol", GeneralCategory::MathSymbol),
+    ("sc", "currency symbol", GeneralCategory::CurrencySymbol),
+    ("sk", "modifier symbol", GeneralCategory::ModifierSymbol),
+    ("so", "other symbol", GeneralCategory::OtherSymbol),
+    ("zs", "space separator", GeneralCategory::SpaceSeparator),
+    ("zl", "line separator", GeneralCategory::LineSeparator),
+    ("zp", "paragraph separator", GeneralCategory::ParagraphSeparator),
+    ("cc", "control", GeneralCategory::Control),
+    ("cf", "format", GeneralCategory::Format),
+    ("cs", "surrogate", GeneralCategory::Surrogate),
+    ("co", "private use", GeneralCategory::PrivateUse),
+    ("cn", "unassigned", GeneralCategory::Unassigned),
+];
+
+/// A special sequence with a standard meaning, recognized by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    AnyCharacter,
+    Letter,
+    Digit,
+    Whitespace,
+    EndOfInput,
+    Category(GeneralCategory),
+}
+
+/// Normalizes the content of a special sequence, so that `? Any  Character ?`
+/// and `?any character?` name the same sequence.
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl Builtin {
+    pub fn from_name(name: &str) -> Option<Builtin> {
+        let name = normalize(name);
+        match name.as_ref() {
+            "any character" => Some(Builtin::AnyCharacter),
+            "letter" => Some(Builtin::Letter),
+            "digit" => Some(Builtin::Digit),
+            "whitespace" => Some(Builtin::Whitespace),
+            "end of input" => Some(Builtin::EndOfInput),
+            name => CATEGORIES
+                .iter()
+                .find(|(short, long, _)| *short == name || *long == name)
+                .map(|(_, _, category)| Builtin::Category(*category)),
+        }
+    }
+
+    /// Number of bytes matched at the start of `input`, which is the rest of
+    /// the checked string.
+    pub fn matches(&self, input: &str) -> Option<usize> {
+        let first = input.chars().next();
+        let matches = match self {
+            Builtin::EndOfInput => return if input.is_empty() { Some(0) } else { None },
+            Builtin::AnyCharacter => first.is_some(),
+            Builtin::Letter => matches!(first, Some(ch) if ch.is_alphabetic()),
+            Builtin::Digit => matches!(first, Some(ch) if ch.is_ascii_digit()),
+            Builtin::Whitespace => matches!(first, Some(ch) if ch.is_whitespace()),
+            Builtin::Category(category) => {
+                matches!(first, Some(ch) if get_general_category(ch) == *category)
+            }
+        };
+        if matches {
+            first.map(char::len_utf8)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the sequence may match without consuming any input.
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, Builtin::EndOfInput)
+    }
+}
+
+/// Host code matching a special sequence at the start of the rest of the
+/// checked string. Returns the number of bytes consumed and, optionally, a
+/// subtree to put in place of the matched text, with spans relative to the
+/// start of the match. Spans reaching past the match are cut short at its end.
+pub type Matcher = dyn Fn(&str) -> Option<(usize, Option<Node>)> + Send + Sync;
+
+#[derive(Clone)]
+struct Registered {
+    matcher: Arc<Matcher>,
+    /// Whether empty matches are rejected, so that the sequence always
+    /// consumes input.
+    consuming: bool,
+}
+
+/// Special sequences recognized during checking, on top of the built-in ones.
+#[derive(Clone, Default)]
+pub struct SpecialRegistry {
+    matchers: BTreeMap<String, Registered>,
+}
+
+impl SpecialRegistry {
+    pub const fn new() -> SpecialRegistry {
+        SpecialRegistry {
+            matchers: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a matcher for the special sequence `name`, overriding any
+    /// built-in sequence of the same name.
+    pub fn insert<F>(&mut self, name: &str, matcher: F)
+    where
+        F: Fn(&str) -> Option<(usize, Option<Node>)> + Send + Sync + 'static,
+    {
+        let matcher = Arc::new(matcher);
+        self.matchers.insert(normalize(name), Registered { matcher, consuming: false });
+    }
+
+    /// Registers a matcher for the special sequence `name` that never matches
+    /// empty input, which lets it start a recursive rule or be repeated. An
+    /// empty match it returns counts as a failure.
+    pub fn insert_consuming<F>(&mut self, name: &str, matcher: F)
+    where
+        F: Fn(&str) -> Option<(usize, Option<Node>)> + Send + Sync + 'static,
+    {
+        let matcher = Arc::new(matcher);
+        self.matchers.insert(normalize(name), Registered { matcher, consuming: true });
+    }
+
+    /// Whether `name` is either registered or built in.
+    pub fn contains(&self, name: &str) -> bool {
+        self.matchers.contains_key(&normalize(name)) || Builtin::from_name(name).is_some()
+    }
+
+    /// Whether the special sequence `name` may match without consuming
+    /// input. Matchers registered with [`insert`](Self::insert) are assumed
+    /// to, as are sequences that are neither registered nor built in.
+    pub fn is_nullable(&self, name: &str) -> bool {
+        match self.matchers.get(&normalize(name)) {
+            Some(registered) => !registered.consuming,
+            None => match Builtin::from_name(name) {
+                Some(builtin) => builtin.is_nullable(),
+                None => true,
+            },
+        }
+    }
+
+    /// Matches the special sequence `name` at the start of `input`.
+    pub fn matches(&self, name: &str, input: &str) -> Option<(usize, Option<Node>)> {
+        match self.matchers.get(&normalize(name)) {
+            Some(Registered { matcher, consuming }) => matcher(input)
+                .filter(|(len, _)| input.is_char_boundary(*len))
+                .filter(|(len, _)| !consuming || *len > 0),
+            None => Builtin::from_name(name)?.matches(input).map(|len| (len, None)),
+        }
+    }
+}
+
+impl fmt::Debug for SpecialRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.matchers.keys()).finish()
+    }
+}
+
+/// Turns the result of a match at byte `offset` into the nodes it
+/// contributes, moving a returned subtree to where it was matched.
+pub(crate) fn matched_nodes(
+    input: &str,
+    locator: &Locator,
+    offset: usize,
+    (len, node): (usize, Option<Node>),
+) -> Option<Vec<Node>> {
+    match node {
+        Some(node) => Some(vec![relocate(node, input, locator, offset, len)]),
+        None if len == 0 => None,
+        None => Some(vec![Node::Terminal(
+            input[offset..offset + len].to_owned(),
+            locator.span(offset, offset + len),
+        )]),
+    }
+}
+
+/// Moves a subtree returned by a matcher to byte `offset`, clamping its
+/// spans to the `len` bytes it matched.
+fn relocate(node: Node, input: &str, locator: &Locator, offset: usize, len: usize) -> Node {
+    let clamp = |relative: usize| {
+        let mut absolute = offset + relative.min(len);
+        while !input.is_char_boundary(absolute) {
+            absolute -= 1;
+        }
+        absolute
+    };
+    let span = |span: Span| locator.span(clamp(span.from.offset), clamp(span.to.offset));
+    match node {
+        Node::Nonterminal(name, children, old) => Node::Nonterminal(
+            name,
+            children
+                .into_iter()
+                .map(|child| relocate(child, input, locator, offset, len))
+                .collect(),
+            span(old),
+        ),
+        Node::Terminal(content, old) => Node::Terminal(content, span(old)),
+    }
+}
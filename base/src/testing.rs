@@ -0,0 +1,26 @@
+//! Helpers shared by the tests of the checking engines.
+
+use crate::checker::Node;
+use crate::span::Span;
+
+pub fn leaf(content: &str) -> Node {
+    Node::Terminal(content.to_owned(), Span::default())
+}
+
+pub fn branch(name: &str, children: Vec<Node>) -> Node {
+    Node::Nonterminal(name.to_owned(), children, Span::default())
+}
+
+/// Resets the spans of a tree, so that only its shape is compared.
+pub fn strip(node: Node) -> Node {
+    match node {
+        Node::Nonterminal(name, children, _) => {
+            branch(&name, children.into_iter().map(strip).collect())
+        }
+        Node::Terminal(content, _) => leaf(&content),
+    }
+}
+
+pub fn strip_all(nodes: Vec<Node>) -> Vec<Node> {
+    nodes.into_iter().map(strip).collect()
+}
@@ -0,0 +1,23 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EliminationError {
+    /// Rules still left-recursive after the transform, such as those whose
+    /// recursion hides behind an expression that may match empty input.
+    pub rules: Vec<String>,
+}
+
+impl fmt::Display for EliminationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.rules.as_slice() {
+            [rule] => write!(f, "left recursion of rule {} can't be eliminated", rule),
+            rules => write!(
+                f,
+                "left recursion of rules {} can't be eliminated",
+                rules.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EliminationError {}
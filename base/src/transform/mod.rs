@@ -0,0 +1,238 @@
+pub mod error;
+#[cfg(test)]
+mod tests;
+
+use super::analysis::{left_corners, left_recursion, nullable_rules};
+use super::ast::{Expression, Grammar};
+use super::builder::{alternative, sequence};
+use super::checker::Node;
+use super::span::Span;
+use super::special::SpecialRegistry;
+pub use error::EliminationError;
+use std::collections::{HashMap, HashSet};
+
+/// A grammar rewritten without left recursion, along with what's needed to
+/// give its trees the shape of the original grammar.
+#[derive(Debug, Clone)]
+pub struct Eliminated {
+    pub grammar: Grammar,
+    /// The rule each of the rules introduced by the transform continues.
+    tails: HashMap<String, String>,
+}
+
+fn alternatives(expression: &Expression) -> Vec<Expression> {
+    match expression {
+        Expression::Alternative {
+            first: box first,
+            second: box second,
+            rest,
+        } => {
+            let mut alternatives = vec![first.clone(), second.clone()];
+            alternatives.extend(rest.iter().cloned());
+            alternatives
+        }
+        expression => vec![expression.clone()],
+    }
+}
+
+fn elements(expression: &Expression) -> Vec<Expression> {
+    match expression {
+        Expression::Sequence {
+            first: box first,
+            second: box second,
+            rest,
+        } => {
+            let mut elements = vec![first.clone(), second.clone()];
+            elements.extend(rest.iter().cloned());
+            elements
+        }
+        Expression::Empty => Vec::new(),
+        expression => vec![expression.clone()],
+    }
+}
+
+/// Splits an alternative into the rule it starts with and what follows it.
+fn split_leading(alternative: &Expression) -> Option<(String, Vec<Expression>)> {
+    let mut elements = elements(alternative);
+    match elements.first() {
+        Some(Expression::Nonterminal(identifier)) => {
+            let identifier = identifier.clone();
+            elements.remove(0);
+            Some((identifier, elements))
+        }
+        _ => None,
+    }
+}
+
+/// Whether checking rule `from` may lead to checking rule `to` before any
+/// input is consumed.
+fn left_reaches(
+    grammar: &Grammar,
+    nullable: &HashSet<String>,
+    specials: &SpecialRegistry,
+    from: &str,
+    to: &str,
+) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![from];
+    while let Some(rule) = pending.pop() {
+        if let Some(expression) = grammar.get(rule) {
+            for corner in left_corners(expression, nullable, specials) {
+                if corner == to {
+                    return true;
+                }
+                if visited.insert(corner) {
+                    pending.push(corner);
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Replaces `earlier` at the start of every alternative of `rule` with each
+/// of the alternatives of `earlier`.
+fn substitute(grammar: &mut Grammar, rule: &str, earlier: &str) {
+    let replacements = alternatives(&grammar[earlier]);
+    let mut substituted = Vec::new();
+    for alternative in alternatives(&grammar[rule]) {
+        match split_leading(&alternative) {
+            Some((identifier, rest)) if identifier == earlier => {
+                for replacement in replacements.iter() {
+                    let mut elements = elements(replacement);
+                    elements.extend(rest.iter().cloned());
+                    substituted.push(sequence(elements));
+                }
+            }
+            _ => substituted.push(alternative),
+        }
+    }
+    grammar.insert(rule.to_owned(), alternative(substituted));
+}
+
+/// Rewrites `a = a, x | y;` into `a = y, { atail }; atail = x;`.
+fn eliminate_direct(grammar: &mut Grammar, rule: &str, tails: &mut HashMap<String, String>) {
+    let mut recursive = Vec::new();
+    let mut others = Vec::new();
+    for alternative in alternatives(&grammar[rule]) {
+        match split_leading(&alternative) {
+            Some((identifier, rest)) if identifier == rule => {
+                // `a = a | ...` adds nothing to the language of the rule
+                if !rest.is_empty() {
+                    recursive.push(sequence(rest));
+                }
+            }
+            _ => others.push(alternative),
+        }
+    }
+    if others.is_empty() || others.len() == alternatives(&grammar[rule]).len() {
+        return;
+    }
+    let head = alternative(others);
+    if recursive.is_empty() {
+        grammar.insert(rule.to_owned(), head);
+        return;
+    }
+    // a name the lexer accepts, so that the grammar can be printed as EBNF
+    let mut tail = format!("{}tail", rule);
+    let mut suffix = 1;
+    while grammar.contains_key(&tail) {
+        suffix += 1;
+        tail = format!("{}tail{}", rule, suffix);
+    }
+    let repeated = Expression::Repeated(Box::new(Expression::Nonterminal(tail.clone())));
+    grammar.insert(rule.to_owned(), sequence(vec![head, repeated]));
+    grammar.insert(tail.clone(), alternative(recursive));
+    tails.insert(tail, rule.to_owned());
+}
+
+/// Rewrites direct and indirect left recursion into repetition, so that the
+/// grammar can be checked with the backtracking engine. Indirect recursion
+/// is resolved by inlining the rules it goes through, in the order of their
+/// names.
+///
+/// Fails with the rules whose recursion the transform can't resolve, such as
+/// `a = [ 'x' ], a, 'y' | 'z';`. Special sequences that aren't built in are
+/// assumed to match empty input.
+pub fn eliminate_left_recursion(grammar: &Grammar) -> Result<Eliminated, EliminationError> {
+    eliminate_left_recursion_with(grammar, &SpecialRegistry::new())
+}
+
+/// Like `eliminate_left_recursion`, telling whether special sequences may
+/// match empty input with the registry they are checked with.
+pub fn eliminate_left_recursion_with(
+    grammar: &Grammar,
+    specials: &SpecialRegistry,
+) -> Result<Eliminated, EliminationError> {
+    let mut grammar = grammar.clone();
+    let mut tails = HashMap::new();
+    let mut order = grammar.keys().cloned().collect::<Vec<_>>();
+    order.sort();
+    for (i, rule) in order.iter().enumerate() {
+        let nullable = nullable_rules(&grammar, specials);
+        if !left_reaches(&grammar, &nullable, specials, rule, rule) {
+            continue;
+        }
+        for earlier in order[..i].iter() {
+            if left_reaches(&grammar, &nullable, specials, earlier, rule) {
+                substitute(&mut grammar, rule, earlier);
+            }
+        }
+        eliminate_direct(&mut grammar, rule, &mut tails);
+    }
+
+    let mut rules = left_recursion(&grammar, specials)
+        .into_iter()
+        .map(|mut chain| chain.swap_remove(0))
+        .collect::<Vec<_>>();
+    rules.sort();
+    if rules.is_empty() {
+        Ok(Eliminated { grammar, tails })
+    } else {
+        Err(EliminationError { rules })
+    }
+}
+
+impl Eliminated {
+    /// Rebuilds a tree of the transformed grammar into the left-associative
+    /// shape the original grammar describes, as in `((n + n) + n)`. The rules
+    /// inlined to resolve indirect recursion are missing from the result.
+    pub fn rebuild(&self, node: Node) -> Node {
+        let (name, children, span) = match node {
+            Node::Nonterminal(name, children, span) => (name, children, span),
+            terminal => return terminal,
+        };
+        let mut head = Vec::new();
+        let mut continuations: Vec<(Vec<Node>, Span)> = Vec::new();
+        for child in children.into_iter().map(|child| self.rebuild(child)) {
+            let current = match continuations.last_mut() {
+                Some((children, _)) => children,
+                None => &mut head,
+            };
+            match child {
+                Node::Nonterminal(child_name, grandchildren, child_span)
+                    if self.tails.contains_key(&child_name) =>
+                {
+                    if self.tails[&child_name] == name {
+                        continuations.push((grandchildren, child_span));
+                    } else {
+                        current.extend(grandchildren);
+                    }
+                }
+                child => current.push(child),
+            }
+        }
+        if continuations.is_empty() {
+            return Node::Nonterminal(name, head, span);
+        }
+        let from = span.from;
+        let to = head.last().map_or(from, |child| child.span().to);
+        let mut node = Node::Nonterminal(name.clone(), head, Span { from, to });
+        for (children, continuation) in continuations {
+            let mut nodes = vec![node];
+            nodes.extend(children);
+            node = Node::Nonterminal(name.clone(), nodes, Span { from, to: continuation.to });
+        }
+        node
+    }
+}
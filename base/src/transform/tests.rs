@@ -0,0 +1,131 @@
+use super::{eliminate_left_recursion, eliminate_left_recursion_with, EliminationError};
+use crate::ast::{Expression, Grammar};
+use crate::builder::{alternative, nonterminal, sequence, special, terminal};
+use crate::checker::check;
+use crate::earley::check_all;
+use crate::special::SpecialRegistry;
+use crate::testing::strip;
+
+#[test]
+fn test_direct_left_recursion() {
+    // e = e, '+', t | e, '-', t | t;
+    // t = 'n';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "e".to_owned(),
+        alternative(vec![
+            sequence(vec![nonterminal("e"), terminal("+"), nonterminal("t")]),
+            sequence(vec![nonterminal("e"), terminal("-"), nonterminal("t")]),
+            nonterminal("t"),
+        ]),
+    );
+    grammar.insert("t".to_owned(), terminal("n"));
+    let eliminated = eliminate_left_recursion(&grammar).unwrap();
+    assert_eq!(
+        eliminated.grammar["e"],
+        sequence(vec![
+            nonterminal("t"),
+            Expression::Repeated(Box::new(nonterminal("etail"))),
+        ])
+    );
+    for input in ["n", "n+n-n", "n-n+n+n"] {
+        let node = check(input, &eliminated.grammar, "e").unwrap();
+        let rebuilt = eliminated.rebuild(node);
+        assert_eq!(rebuilt.span().to.offset, input.len());
+        assert_eq!(
+            Ok(vec![strip(rebuilt)]),
+            check_all(input, &grammar, "e").map(|nodes| nodes.into_iter().map(strip).collect())
+        );
+    }
+    assert!(check("n+", &eliminated.grammar, "e").is_err());
+}
+
+#[test]
+fn test_indirect_left_recursion() {
+    // a = b, 'x' | 'y';
+    // b = a, 'z' | 'w';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "a".to_owned(),
+        alternative(vec![
+            sequence(vec![nonterminal("b"), terminal("x")]),
+            terminal("y"),
+        ]),
+    );
+    grammar.insert(
+        "b".to_owned(),
+        alternative(vec![
+            sequence(vec![nonterminal("a"), terminal("z")]),
+            terminal("w"),
+        ]),
+    );
+    let eliminated = eliminate_left_recursion(&grammar).unwrap();
+    for input in ["y", "wx", "yzx", "wxzx", "yzxzx", "yz", "wxz", "x", ""] {
+        assert_eq!(
+            check(input, &eliminated.grammar, "a").is_ok(),
+            check_all(input, &grammar, "a").is_ok(),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_hidden_left_recursion() {
+    // a = [ 'x' ], a, 'y' | 'z';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "a".to_owned(),
+        alternative(vec![
+            sequence(vec![
+                Expression::Optional(Box::new(terminal("x"))),
+                nonterminal("a"),
+                terminal("y"),
+            ]),
+            terminal("z"),
+        ]),
+    );
+    assert_eq!(
+        eliminate_left_recursion(&grammar).unwrap_err(),
+        EliminationError {
+            rules: vec!["a".to_owned()],
+        }
+    );
+}
+
+#[test]
+fn test_tail_names() {
+    // a = a, 'x' | atail;
+    // atail = 'y';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "a".to_owned(),
+        alternative(vec![
+            sequence(vec![nonterminal("a"), terminal("x")]),
+            nonterminal("atail"),
+        ]),
+    );
+    grammar.insert("atail".to_owned(), terminal("y"));
+    let eliminated = eliminate_left_recursion(&grammar).unwrap();
+    assert_eq!(eliminated.grammar["atail2"], terminal("x"));
+}
+
+#[test]
+fn test_nullable_special_sequences() {
+    // a = ?spaces?, a, 'x' | 'y';
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "a".to_owned(),
+        alternative(vec![
+            sequence(vec![special("spaces"), nonterminal("a"), terminal("x")]),
+            terminal("y"),
+        ]),
+    );
+    let spaces = |input: &str| Some((input.len() - input.trim_start().len(), None));
+    let mut specials = SpecialRegistry::new();
+    specials.insert("spaces", spaces);
+    assert!(eliminate_left_recursion_with(&grammar, &specials).is_err());
+    specials.insert_consuming("spaces", spaces);
+    let eliminated = eliminate_left_recursion_with(&grammar, &specials).unwrap();
+    assert_eq!(eliminated.grammar, grammar);
+}
@@ -0,0 +1,302 @@
+//! Traversals of [`Expression`] trees. Each trait has a method for every
+//! kind of expression, which by default only walks into its children, so an
+//! analysis or rewrite needs to override the methods of the expressions it
+//! cares about alone.
+
+use super::ast::Expression;
+
+#[cfg(test)]
+mod tests;
+
+/// Walks an expression by reference.
+pub trait Visitor<'a> {
+    fn visit_expression(&mut self, expression: &'a Expression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_alternative(
+        &mut self,
+        first: &'a Expression,
+        second: &'a Expression,
+        rest: &'a [Expression],
+    ) {
+        self.visit_expression(first);
+        self.visit_expression(second);
+        for expression in rest.iter() {
+            self.visit_expression(expression);
+        }
+    }
+
+    fn visit_sequence(
+        &mut self,
+        first: &'a Expression,
+        second: &'a Expression,
+        rest: &'a [Expression],
+    ) {
+        self.visit_expression(first);
+        self.visit_expression(second);
+        for expression in rest.iter() {
+            self.visit_expression(expression);
+        }
+    }
+
+    fn visit_optional(&mut self, inner: &'a Expression) {
+        self.visit_expression(inner);
+    }
+
+    fn visit_repeated(&mut self, inner: &'a Expression) {
+        self.visit_expression(inner);
+    }
+
+    fn visit_factor(&mut self, _count: usize, primary: &'a Expression) {
+        self.visit_expression(primary);
+    }
+
+    fn visit_exception(&mut self, subject: &'a Expression, restriction: &'a Expression) {
+        self.visit_expression(subject);
+        self.visit_expression(restriction);
+    }
+
+    fn visit_nonterminal(&mut self, _identifier: &'a str) {}
+
+    fn visit_terminal(&mut self, _content: &'a str) {}
+
+    fn visit_special(&mut self, _content: &'a str) {}
+
+    fn visit_empty(&mut self) {}
+}
+
+/// Calls the method of `visitor` for the kind of `expression`.
+pub fn walk_expression<'a, V>(visitor: &mut V, expression: &'a Expression)
+where
+    V: Visitor<'a> + ?Sized,
+{
+    match expression {
+        Expression::Alternative {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_alternative(first, second, rest),
+        Expression::Sequence {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_sequence(first, second, rest),
+        Expression::Optional(box inner) => visitor.visit_optional(inner),
+        Expression::Repeated(box inner) => visitor.visit_repeated(inner),
+        Expression::Factor {
+            count,
+            primary: box primary,
+        } => visitor.visit_factor(*count, primary),
+        Expression::Exception {
+            subject: box subject,
+            restriction: box restriction,
+        } => visitor.visit_exception(subject, restriction),
+        Expression::Nonterminal(identifier) => visitor.visit_nonterminal(identifier),
+        Expression::Terminal(content) => visitor.visit_terminal(content),
+        Expression::Special(content) => visitor.visit_special(content),
+        Expression::Empty => visitor.visit_empty(),
+    }
+}
+
+/// Walks an expression by mutable reference, to change it in place.
+pub trait VisitorMut {
+    fn visit_expression_mut(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression);
+    }
+
+    fn visit_alternative_mut(
+        &mut self,
+        first: &mut Expression,
+        second: &mut Expression,
+        rest: &mut Vec<Expression>,
+    ) {
+        self.visit_expression_mut(first);
+        self.visit_expression_mut(second);
+        for expression in rest.iter_mut() {
+            self.visit_expression_mut(expression);
+        }
+    }
+
+    fn visit_sequence_mut(
+        &mut self,
+        first: &mut Expression,
+        second: &mut Expression,
+        rest: &mut Vec<Expression>,
+    ) {
+        self.visit_expression_mut(first);
+        self.visit_expression_mut(second);
+        for expression in rest.iter_mut() {
+            self.visit_expression_mut(expression);
+        }
+    }
+
+    fn visit_optional_mut(&mut self, inner: &mut Expression) {
+        self.visit_expression_mut(inner);
+    }
+
+    fn visit_repeated_mut(&mut self, inner: &mut Expression) {
+        self.visit_expression_mut(inner);
+    }
+
+    fn visit_factor_mut(&mut self, _count: &mut usize, primary: &mut Expression) {
+        self.visit_expression_mut(primary);
+    }
+
+    fn visit_exception_mut(&mut self, subject: &mut Expression, restriction: &mut Expression) {
+        self.visit_expression_mut(subject);
+        self.visit_expression_mut(restriction);
+    }
+
+    fn visit_nonterminal_mut(&mut self, _identifier: &mut String) {}
+
+    fn visit_terminal_mut(&mut self, _content: &mut String) {}
+
+    fn visit_special_mut(&mut self, _content: &mut String) {}
+
+    fn visit_empty_mut(&mut self) {}
+}
+
+/// Calls the method of `visitor` for the kind of `expression`.
+pub fn walk_expression_mut<V>(visitor: &mut V, expression: &mut Expression)
+where
+    V: VisitorMut + ?Sized,
+{
+    match expression {
+        Expression::Alternative {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_alternative_mut(first, second, rest),
+        Expression::Sequence {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_sequence_mut(first, second, rest),
+        Expression::Optional(box inner) => visitor.visit_optional_mut(inner),
+        Expression::Repeated(box inner) => visitor.visit_repeated_mut(inner),
+        Expression::Factor {
+            count,
+            primary: box primary,
+        } => visitor.visit_factor_mut(count, primary),
+        Expression::Exception {
+            subject: box subject,
+            restriction: box restriction,
+        } => visitor.visit_exception_mut(subject, restriction),
+        Expression::Nonterminal(identifier) => visitor.visit_nonterminal_mut(identifier),
+        Expression::Terminal(content) => visitor.visit_terminal_mut(content),
+        Expression::Special(content) => visitor.visit_special_mut(content),
+        Expression::Empty => visitor.visit_empty_mut(),
+    }
+}
+
+/// Rebuilds an expression out of its parts, each of which may be replaced
+/// with an expression of another kind.
+pub trait Fold {
+    fn fold_expression(&mut self, expression: Expression) -> Expression {
+        walk_fold(self, expression)
+    }
+
+    fn fold_alternative(
+        &mut self,
+        first: Expression,
+        second: Expression,
+        rest: Vec<Expression>,
+    ) -> Expression {
+        Expression::Alternative {
+            first: Box::new(self.fold_expression(first)),
+            second: Box::new(self.fold_expression(second)),
+            rest: rest
+                .into_iter()
+                .map(|expression| self.fold_expression(expression))
+                .collect(),
+        }
+    }
+
+    fn fold_sequence(
+        &mut self,
+        first: Expression,
+        second: Expression,
+        rest: Vec<Expression>,
+    ) -> Expression {
+        Expression::Sequence {
+            first: Box::new(self.fold_expression(first)),
+            second: Box::new(self.fold_expression(second)),
+            rest: rest
+                .into_iter()
+                .map(|expression| self.fold_expression(expression))
+                .collect(),
+        }
+    }
+
+    fn fold_optional(&mut self, inner: Expression) -> Expression {
+        Expression::Optional(Box::new(self.fold_expression(inner)))
+    }
+
+    fn fold_repeated(&mut self, inner: Expression) -> Expression {
+        Expression::Repeated(Box::new(self.fold_expression(inner)))
+    }
+
+    fn fold_factor(&mut self, count: usize, primary: Expression) -> Expression {
+        Expression::Factor {
+            count,
+            primary: Box::new(self.fold_expression(primary)),
+        }
+    }
+
+    fn fold_exception(&mut self, subject: Expression, restriction: Expression) -> Expression {
+        Expression::Exception {
+            subject: Box::new(self.fold_expression(subject)),
+            restriction: Box::new(self.fold_expression(restriction)),
+        }
+    }
+
+    fn fold_nonterminal(&mut self, identifier: String) -> Expression {
+        Expression::Nonterminal(identifier)
+    }
+
+    fn fold_terminal(&mut self, content: String) -> Expression {
+        Expression::Terminal(content)
+    }
+
+    fn fold_special(&mut self, content: String) -> Expression {
+        Expression::Special(content)
+    }
+
+    fn fold_empty(&mut self) -> Expression {
+        Expression::Empty
+    }
+}
+
+/// Calls the method of `folder` for the kind of `expression`.
+pub fn walk_fold<F>(folder: &mut F, expression: Expression) -> Expression
+where
+    F: Fold + ?Sized,
+{
+    match expression {
+        Expression::Alternative {
+            first: box first,
+            second: box second,
+            rest,
+        } => folder.fold_alternative(first, second, rest),
+        Expression::Sequence {
+            first: box first,
+            second: box second,
+            rest,
+        } => folder.fold_sequence(first, second, rest),
+        Expression::Optional(box inner) => folder.fold_optional(inner),
+        Expression::Repeated(box inner) => folder.fold_repeated(inner),
+        Expression::Factor {
+            count,
+            primary: box primary,
+        } => folder.fold_factor(count, primary),
+        Expression::Exception {
+            subject: box subject,
+            restriction: box restriction,
+        } => folder.fold_exception(subject, restriction),
+        Expression::Nonterminal(identifier) => folder.fold_nonterminal(identifier),
+        Expression::Terminal(content) => folder.fold_terminal(content),
+        Expression::Special(content) => folder.fold_special(content),
+        Expression::Empty => folder.fold_empty(),
+    }
+}
@@ -0,0 +1,97 @@
+use super::{Fold, Visitor, VisitorMut};
+use crate::ast::Expression;
+use crate::builder::{empty, nonterminal, special, terminal};
+use crate::{alt, seq};
+
+// a, [ b - 'x' ], { 2 * ?digit? | a }
+fn expression() -> Expression {
+    seq![
+        nonterminal("a"),
+        nonterminal("b").except(terminal("x")).opt(),
+        alt![special("digit").times(2), nonterminal("a")].rep(),
+    ]
+}
+
+#[test]
+fn test_visitor() {
+    #[derive(Default)]
+    struct Collect<'a> {
+        nonterminals: Vec<&'a str>,
+        leaves: usize,
+    }
+
+    impl<'a> Visitor<'a> for Collect<'a> {
+        fn visit_nonterminal(&mut self, identifier: &'a str) {
+            self.nonterminals.push(identifier);
+            self.leaves += 1;
+        }
+
+        fn visit_terminal(&mut self, _: &'a str) {
+            self.leaves += 1;
+        }
+
+        fn visit_special(&mut self, _: &'a str) {
+            self.leaves += 1;
+        }
+    }
+
+    let expression = expression();
+    let mut collect = Collect::default();
+    collect.visit_expression(&expression);
+    assert_eq!(collect.nonterminals, vec!["a", "b", "a"]);
+    assert_eq!(collect.leaves, 5);
+}
+
+#[test]
+fn test_visitor_mut() {
+    struct Rename;
+
+    impl VisitorMut for Rename {
+        fn visit_nonterminal_mut(&mut self, identifier: &mut String) {
+            if identifier == "a" {
+                *identifier = "c".to_owned();
+            }
+        }
+
+        fn visit_factor_mut(&mut self, count: &mut usize, primary: &mut Expression) {
+            *count += 1;
+            self.visit_expression_mut(primary);
+        }
+    }
+
+    let mut expression = expression();
+    Rename.visit_expression_mut(&mut expression);
+    assert_eq!(
+        expression,
+        seq![
+            nonterminal("c"),
+            nonterminal("b").except(terminal("x")).opt(),
+            alt![special("digit").times(3), nonterminal("c")].rep(),
+        ]
+    );
+}
+
+#[test]
+fn test_fold() {
+    // rewrites [ x ] into x | and { x } into a rule r = x, r | ;
+    struct Desugar;
+
+    impl Fold for Desugar {
+        fn fold_optional(&mut self, inner: Expression) -> Expression {
+            alt![self.fold_expression(inner), empty()]
+        }
+
+        fn fold_repeated(&mut self, _: Expression) -> Expression {
+            nonterminal("r")
+        }
+    }
+
+    assert_eq!(
+        Desugar.fold_expression(expression()),
+        seq![
+            nonterminal("a"),
+            alt![nonterminal("b").except(terminal("x")), empty()],
+            nonterminal("r"),
+        ]
+    );
+}
@@ -1,3 +1,4 @@
+use structopt::clap::{AppSettings, ErrorKind};
 use structopt::StructOpt;
 use std::path::PathBuf;
 use std::fs;
@@ -6,16 +7,56 @@ use ebnf;
 use std::io::Write;
 
 #[derive(Debug, StructOpt)]
+#[structopt(setting = AppSettings::ArgsNegateSubcommands)]
 pub struct Config {
     /// Grammar file path
     #[structopt(name = "GRAMMAR FILE", parse(from_os_str))]
-    pub grammar_path: PathBuf,
-    /// Initial production rule
+    pub grammar_path: Option<PathBuf>,
+    /// Initial production rule, the first rule of the grammar by default
     #[structopt(name = "INITIAL RULE")]
-    pub initial_rule: String,
+    pub initial_rule: Option<String>,
     /// Test string file path
     #[structopt(short = "t", long = "test", name = "TEST STRING FILE", parse(from_os_str))]
     pub test_string_path: Option<PathBuf>,
+    /// Memoize rule results while checking
+    #[structopt(long = "packrat")]
+    pub packrat: bool,
+    /// Check with the Earley engine, which accepts left-recursive and ambiguous grammars
+    #[structopt(long = "earley")]
+    pub earley: bool,
+    /// Rewrite left-recursive rules into repetitions, so that the grammar can be checked without the Earley engine
+    #[structopt(long = "eliminate-left-recursion")]
+    pub eliminate_left_recursion: bool,
+    /// Highlight diagnostics with ANSI colors
+    #[structopt(long = "color")]
+    pub color: bool,
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Command {
+    /// Formats a grammar file in the canonical style
+    Fmt {
+        /// Grammar file path
+        #[structopt(name = "GRAMMAR FILE", parse(from_os_str))]
+        grammar_path: PathBuf,
+        /// Report whether the file is formatted instead of rewriting it
+        #[structopt(long = "check")]
+        check: bool,
+    },
+    /// Precompiles a grammar file into a binary one, which loads without being parsed again
+    Compile {
+        /// Grammar file path
+        #[structopt(name = "GRAMMAR FILE", parse(from_os_str))]
+        grammar_path: PathBuf,
+        /// Output file path, the grammar file path with the ppg extension by default
+        #[structopt(short = "o", long = "output", name = "OUTPUT FILE", parse(from_os_str))]
+        output_path: Option<PathBuf>,
+        /// Accept left-recursive rules, for grammars checked with --earley or --eliminate-left-recursion
+        #[structopt(long = "allow-left-recursion")]
+        allow_left_recursion: bool,
+    },
 }
 
 pub fn read() -> String {
@@ -27,32 +68,201 @@ pub fn read() -> String {
     return buffer;
 }
 
+fn fmt(grammar_path: PathBuf, check: bool, color: bool) {
+    let grammar = fs::read_to_string(&grammar_path).unwrap();
+    let formatted = match ebnf::formatter::format(&grammar) {
+        Ok(formatted) => formatted,
+        Err(errors) => {
+            for e in errors {
+                println!("{}", e.render(&grammar, color));
+            }
+            std::process::exit(1);
+        }
+    };
+    if formatted == grammar {
+        return;
+    }
+    if check {
+        println!("{} is not formatted", grammar_path.display());
+        std::process::exit(1);
+    }
+    fs::write(&grammar_path, formatted).unwrap();
+}
+
+fn compile(
+    grammar_path: PathBuf,
+    output_path: Option<PathBuf>,
+    allow_left_recursion: bool,
+    color: bool,
+) {
+    let grammar = fs::read_to_string(&grammar_path).unwrap();
+    let options = ebnf::Options {
+        allow_left_recursion,
+        ..ebnf::Options::default()
+    };
+    let compiled = match ebnf::parse_with(&grammar, &options) {
+        Ok((compiled, warnings)) => {
+            for w in warnings {
+                println!("{}", w.render(&grammar, color));
+            }
+            compiled
+        }
+        Err(errors) => {
+            for e in errors {
+                println!("{}", e.render(&grammar, color));
+            }
+            std::process::exit(1);
+        }
+    };
+    let output_path = output_path.unwrap_or_else(|| grammar_path.with_extension("ppg"));
+    fs::write(&output_path, base::binary::encode(&compiled)).unwrap();
+}
+
+/// Reads a grammar, either precompiled or from source. Returns `None` if it's
+/// invalid, after reporting why.
+fn load(grammar_path: PathBuf, config: &Config) -> Option<base::Grammar> {
+    let bytes = fs::read(grammar_path).unwrap();
+    if bytes.starts_with(&base::binary::MAGIC) {
+        let mut grammar = match base::binary::decode(&bytes) {
+            Ok(grammar) => grammar,
+            Err(e) => {
+                println!("Error: {}", e);
+                return None;
+            }
+        };
+        if grammar.is_empty() {
+            println!("Error: grammar has no rules");
+            return None;
+        }
+        // grammars compiled with --allow-left-recursion can't be backtracked
+        if !config.earley && !config.eliminate_left_recursion {
+            let left_recursive = match base::eliminate_left_recursion(&grammar) {
+                Ok(eliminated) => eliminated.grammar != grammar,
+                Err(_) => true,
+            };
+            if left_recursive {
+                println!(
+                    "Error: grammar is left recursive, check it with --earley or \
+                     --eliminate-left-recursion"
+                );
+                return None;
+            }
+        }
+        if let Some(initial_rule) = &config.initial_rule {
+            if !grammar.contains_key(initial_rule) {
+                println!("Error: rule {} is undefined", initial_rule);
+                return None;
+            }
+            grammar.set_start(initial_rule);
+        }
+        println!("Successfully loaded the provided grammar\n");
+        return Some(grammar);
+    }
+
+    let grammar = String::from_utf8(bytes).unwrap();
+    let parse_options = ebnf::Options {
+        allow_left_recursion: config.earley || config.eliminate_left_recursion,
+        start: config.initial_rule.clone(),
+        ..ebnf::Options::default()
+    };
+    match ebnf::parse_with(&grammar, &parse_options) {
+        Ok((g, warnings)) => {
+            for w in warnings {
+                println!("{}", w.render(&grammar, config.color));
+            }
+            println!("Successfully parsed the provided grammar\n");
+            Some(g)
+        },
+        Err(errors) => {
+            for e in errors {
+                println!("{}", e.render(&grammar, config.color));
+            }
+            None
+        }
+    }
+}
+
 fn main() {
     let config = Config::from_args();
 
+    match config.command {
+        Some(Command::Fmt { grammar_path, check }) => {
+            return fmt(grammar_path, check, config.color);
+        }
+        Some(Command::Compile {
+            grammar_path,
+            output_path,
+            allow_left_recursion,
+        }) => {
+            return compile(grammar_path, output_path, allow_left_recursion, config.color);
+        }
+        None => {}
+    }
+    let grammar_path = match config.grammar_path.clone() {
+        Some(grammar_path) => grammar_path,
+        None => structopt::clap::Error::with_description(
+            "a grammar file is required",
+            ErrorKind::MissingRequiredArgument,
+        )
+        .exit(),
+    };
+
     println!("parser-parser 0.1.0\n");
 
-    let grammar = fs::read_to_string(config.grammar_path).unwrap();
-    let grammar = match ebnf::parse(&grammar) {
-        Ok(g) => {
-            println!("Successfully parsed the provided grammar\n");
-            g
-        },
-        Err(e) => {
-            println!("Error: {} at position {}:{}", e, e.span.from.line, e.span.from.column);
-            return;
+    let grammar = match load(grammar_path, &config) {
+        Some(grammar) => grammar,
+        None => return,
+    };
+
+    let initial_rule = grammar.start().unwrap().to_owned();
+
+    let grammar = if config.eliminate_left_recursion {
+        match base::eliminate_left_recursion(&grammar) {
+            Ok(eliminated) => eliminated.grammar,
+            Err(e) => {
+                println!("Error: {}", e);
+                return;
+            }
         }
+    } else {
+        grammar
+    };
+
+    let options = base::Options {
+        packrat: config.packrat,
+        ..base::Options::default()
     };
 
     loop {
         let input = read();
         let input = input[..input.len() - 1].to_owned();
-        match base::check(&input, &grammar, &config.initial_rule) {
-            Some(_) => {
+        let result = if config.earley {
+            base::parse_forest(&input, &grammar, &initial_rule).map(|forest| {
+                for ambiguity in forest.ambiguities() {
+                    println!(
+                        "Ambiguous: rule {} derives {}:{}..{}:{} in {} ways",
+                        ambiguity.rule,
+                        ambiguity.span.from.line,
+                        ambiguity.span.from.column,
+                        ambiguity.span.to.line,
+                        ambiguity.span.to.column,
+                        ambiguity.derivations
+                    );
+                }
+            })
+        } else {
+            base::check_with(&input, &grammar, &initial_rule, &options).map(|_| ())
+        };
+        match result {
+            Ok(_) => {
                 println!("true")
             }
-            None => {
-                println!("false")
+            Err(e) => {
+                println!("false");
+                println!("Error: {}", e);
+                if !e.stack.is_empty() {
+                    println!("  in {}", e.stack.join(" -> "));
+                }
             },
         }
     }
@@ -1,67 +1,196 @@
+use super::cst::{split_trivia, TriviaKind};
 use super::parser::{Expression, Grammar, Production};
-use super::span::Spanned;
+use super::parser::visit::Visitor;
+use super::span::{Location, Span, Spanned};
 
+/// Strips the spans off a valid grammar. The grammar must have passed
+/// [`preprocess`](crate::preprocessor::preprocess), which rejects the
+/// productions the parser recovered from.
 pub fn compile(Spanned { node: grammar, .. }: Spanned<Grammar>) -> base::Grammar {
     grammar
         .productions
         .into_iter()
-        .map(
-            |Spanned {
-                 node:
-                     Production {
-                         lhs: Spanned { node: name, .. },
-                         rhs: expr,
-                     },
-                 ..
-             }: Spanned<Production>|
-             -> (String, base::Expression) { (name, aux(expr)) },
-        )
+        .map(|production| (production.node.lhs.node, lower(&production.node.rhs)))
         .collect()
 }
 
-fn aux(
-    Spanned {
-        node: expression, ..
-    }: Spanned<Expression>,
-) -> base::Expression {
-    match expression {
-        Expression::Alternative {
-            first: box first,
-            second: box second,
-            rest,
-        } => base::Expression::Alternative {
-            first: Box::new(aux(first)),
-            second: Box::new(aux(second)),
-            rest: rest.into_iter().map(aux).collect(),
-        },
-        Expression::Sequence {
-            first: box first,
-            second: box second,
-            rest,
-        } => base::Expression::Sequence {
-            first: Box::new(aux(first)),
-            second: Box::new(aux(second)),
-            rest: rest.into_iter().map(aux).collect(),
-        },
-        Expression::Optional(box inner) => base::Expression::Optional(Box::new(aux(inner))),
-        Expression::Repeated(box inner) => base::Expression::Repeated(Box::new(aux(inner))),
-        Expression::Factor {
-            count: Spanned { node: count, .. },
-            primary: box primary,
-        } => base::Expression::Factor {
-            count,
-            primary: Box::new(aux(primary)),
-        },
-        Expression::Exception {
-            subject: box subject,
-            restriction: box restriction,
-        } => base::Expression::Exception {
-            subject: Box::new(aux(subject)),
-            restriction: Box::new(aux(restriction)),
-        },
-        Expression::Nonterminal(identifier) => base::Expression::Nonterminal(identifier),
-        Expression::Terminal(content) => base::Expression::Terminal(content),
-        Expression::Special(content) => base::Expression::Special(content),
-        Expression::Empty => base::Expression::Empty,
+/// Like [`compile`], but keeps the spans of the rules, along with the
+/// comments right above them in `source`, the text the grammar was parsed
+/// from.
+pub fn compile_with_source(
+    Spanned { node: grammar, .. }: Spanned<Grammar>,
+    source: &str,
+) -> base::Grammar {
+    // the offset of the last location converted, and its offset in characters
+    let mut counted = (0, 0);
+    let mut position = |location: Location| {
+        counted.1 += source[counted.0..location.offset].chars().count();
+        counted.0 = location.offset;
+        base::Position {
+            offset: location.offset,
+            char: counted.1,
+            line: location.line,
+            column: location.column,
+        }
+    };
+
+    let mut compiled = base::Grammar::new();
+    let mut previous = None;
+    for Spanned {
+        node: Production { lhs, rhs },
+        span,
+    } in grammar.productions
+    {
+        let doc = doc_comment(source, previous, span.from.offset);
+        let span = base::Span {
+            from: position(span.from),
+            to: position(span.to),
+        };
+        previous = Some(span.to.offset);
+        compiled.insert_rule(base::Rule {
+            name: lhs.node,
+            expression: lower(&rhs),
+            doc,
+            span: Some(span),
+        });
+    }
+    compiled
+}
+
+/// The comments between the end of the previous production and the start of
+/// the next one, left of its first token or on the lines right above it. The
+/// comments on the line the previous production ends on belong to it.
+fn doc_comment(source: &str, previous: Option<usize>, start: usize) -> Option<String> {
+    let mut trivia = split_trivia(&source[previous.unwrap_or(0)..start]);
+    if previous.is_some() {
+        let end = trivia
+            .iter()
+            .position(|trivia| trivia.kind == TriviaKind::Newline)
+            .unwrap_or(trivia.len());
+        trivia.drain(..end);
+    }
+    let mut comments = Vec::new();
+    let mut newlines = 0;
+    for trivia in trivia.iter().rev() {
+        match trivia.kind {
+            TriviaKind::Whitespace => {}
+            TriviaKind::Newline if newlines > 0 => break,
+            TriviaKind::Newline => newlines += 1,
+            TriviaKind::Comment => {
+                newlines = 0;
+                comments.push(trivia.text[2..trivia.text.len() - 2].trim());
+            }
+            TriviaKind::Skipped => break,
+        }
+    }
+    if comments.is_empty() {
+        return None;
+    }
+    comments.reverse();
+    Some(comments.join("\n"))
+}
+
+/// Builds the `base` expression a spanned expression stands for.
+struct Lowering(base::Expression);
+
+impl Lowering {
+    fn lower(&mut self, expression: &Spanned<Expression>) -> base::Expression {
+        self.visit_expression(expression);
+        std::mem::replace(&mut self.0, base::Expression::Empty)
+    }
+
+    fn lower_all(&mut self, expressions: &[Spanned<Expression>]) -> Vec<base::Expression> {
+        expressions
+            .iter()
+            .map(|expression| self.lower(expression))
+            .collect()
+    }
+}
+
+impl<'a> Visitor<'a> for Lowering {
+    fn visit_alternative(
+        &mut self,
+        first: &'a Spanned<Expression>,
+        second: &'a Spanned<Expression>,
+        rest: &'a [Spanned<Expression>],
+        _: Span,
+    ) {
+        self.0 = base::Expression::Alternative {
+            first: Box::new(self.lower(first)),
+            second: Box::new(self.lower(second)),
+            rest: self.lower_all(rest),
+        };
+    }
+
+    fn visit_sequence(
+        &mut self,
+        first: &'a Spanned<Expression>,
+        second: &'a Spanned<Expression>,
+        rest: &'a [Spanned<Expression>],
+        _: Span,
+    ) {
+        self.0 = base::Expression::Sequence {
+            first: Box::new(self.lower(first)),
+            second: Box::new(self.lower(second)),
+            rest: self.lower_all(rest),
+        };
+    }
+
+    fn visit_optional(&mut self, inner: &'a Spanned<Expression>, _: Span) {
+        self.0 = base::Expression::Optional(Box::new(self.lower(inner)));
     }
+
+    fn visit_repeated(&mut self, inner: &'a Spanned<Expression>, _: Span) {
+        self.0 = base::Expression::Repeated(Box::new(self.lower(inner)));
+    }
+
+    fn visit_factor(
+        &mut self,
+        count: &'a Spanned<usize>,
+        primary: &'a Spanned<Expression>,
+        _: Span,
+    ) {
+        self.0 = base::Expression::Factor {
+            count: count.node,
+            primary: Box::new(self.lower(primary)),
+        };
+    }
+
+    fn visit_exception(
+        &mut self,
+        subject: &'a Spanned<Expression>,
+        restriction: &'a Spanned<Expression>,
+        _: Span,
+    ) {
+        self.0 = base::Expression::Exception {
+            subject: Box::new(self.lower(subject)),
+            restriction: Box::new(self.lower(restriction)),
+        };
+    }
+
+    fn visit_nonterminal(&mut self, identifier: &'a str, _: Span) {
+        self.0 = base::Expression::Nonterminal(identifier.to_owned());
+    }
+
+    fn visit_terminal(&mut self, content: &'a str, _: Span) {
+        self.0 = base::Expression::Terminal(content.to_owned());
+    }
+
+    fn visit_special(&mut self, content: &'a str, _: Span) {
+        self.0 = base::Expression::Special(content.to_owned());
+    }
+
+    fn visit_empty(&mut self, _: Span) {
+        self.0 = base::Expression::Empty;
+    }
+
+    fn visit_error(&mut self, _: Span) {
+        unreachable!("preprocessing rejects grammars with syntax errors")
+    }
+}
+
+/// The `base` expression a spanned expression without syntax errors stands
+/// for.
+pub(crate) fn lower(expression: &Spanned<Expression>) -> base::Expression {
+    Lowering(base::Expression::Empty).lower(expression)
 }
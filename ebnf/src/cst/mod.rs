@@ -0,0 +1,209 @@
+use super::error::Error;
+use super::lexer::{self, Token};
+use super::parser::{self, Production};
+use super::span::{Span, Spanned};
+use std::fmt;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    Comment,
+    /// Text the lexer rejected.
+    Skipped,
+}
+
+/// Text between tokens that has no meaning in the grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+}
+
+/// A token along with its exact text and the trivia around it. The trivia on
+/// the same line after a token trails it, the rest leads the next token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Leaf {
+    pub token: Token,
+    pub span: Span,
+    pub text: String,
+    pub leading: Vec<Trivia>,
+    pub trailing: Vec<Trivia>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Production {
+        production: Spanned<Production>,
+        tokens: Vec<Leaf>,
+    },
+    /// A token the parser skipped over outside of any production.
+    Token(Leaf),
+}
+
+/// A grammar that keeps every byte of its source, such that printing it
+/// reproduces the source exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cst {
+    pub nodes: Vec<Node>,
+    /// The trivia after the last token.
+    pub trailing: Vec<Trivia>,
+}
+
+fn comment_length(text: &str) -> usize {
+    let mut depth = 0;
+    let mut i = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        if text[i..].starts_with("(*)") {
+            i += 3;
+        } else if text[i..].starts_with("(*") {
+            depth += 1;
+            i += 2;
+        } else if text[i..].starts_with("*)") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += ch.len_utf8();
+        }
+    }
+    text.len()
+}
+
+fn is_comment(text: &str) -> bool {
+    text.starts_with("(*") && !text.starts_with("(*)")
+}
+
+pub(crate) fn split_trivia(text: &str) -> Vec<Trivia> {
+    let mut trivia = Vec::new();
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        let (kind, length) = if rest.starts_with("\r\n") {
+            (TriviaKind::Newline, 2)
+        } else if ch == '\n' || ch == '\r' {
+            (TriviaKind::Newline, 1)
+        } else if ch.is_whitespace() {
+            let length = rest
+                .find(|ch: char| !ch.is_whitespace() || ch == '\n' || ch == '\r')
+                .unwrap_or(rest.len());
+            (TriviaKind::Whitespace, length)
+        } else if is_comment(rest) {
+            (TriviaKind::Comment, comment_length(rest))
+        } else {
+            let length = rest
+                .char_indices()
+                .skip(1)
+                .find(|(i, ch)| ch.is_whitespace() || is_comment(&rest[*i..]))
+                .map_or(rest.len(), |(i, _)| i);
+            (TriviaKind::Skipped, length)
+        };
+        trivia.push(Trivia {
+            kind,
+            text: rest[..length].to_owned(),
+        });
+        rest = &rest[length..];
+    }
+    trivia
+}
+
+/// Hands the trivia up to the first line break over to the previous token.
+fn attach_trailing(previous: Option<&mut Leaf>, trivia: &mut Vec<Trivia>) {
+    if let Some(previous) = previous {
+        let end = trivia
+            .iter()
+            .position(|trivia| trivia.kind == TriviaKind::Newline)
+            .unwrap_or(trivia.len());
+        previous.trailing = trivia.drain(..end).collect();
+    }
+}
+
+/// Parses a grammar without losing any of its source, recovering from errors
+/// the same way [`crate::parse`] does.
+pub fn parse(input: &str) -> (Cst, Vec<Error>) {
+    let (tokens, lexer_errors) = lexer::lex(input);
+    let (grammar, parser_errors) = parser::parse(&tokens);
+
+    let mut leaves: Vec<Leaf> = Vec::new();
+    let mut position = 0;
+    for Spanned { node: token, span } in tokens {
+        let mut leading = split_trivia(&input[position..span.from.offset]);
+        attach_trailing(leaves.last_mut(), &mut leading);
+        leaves.push(Leaf {
+            token,
+            span,
+            text: input[span.from.offset..span.to.offset].to_owned(),
+            leading,
+            trailing: Vec::new(),
+        });
+        position = span.to.offset;
+    }
+    let mut trailing = split_trivia(&input[position..]);
+    attach_trailing(leaves.last_mut(), &mut trailing);
+
+    let mut leaves = leaves.into_iter().peekable();
+    let mut nodes = Vec::new();
+    for production in grammar.node.productions {
+        while let Some(leaf) =
+            leaves.next_if(|leaf| leaf.span.from.offset < production.span.from.offset)
+        {
+            nodes.push(Node::Token(leaf));
+        }
+        let mut tokens = Vec::new();
+        while let Some(leaf) =
+            leaves.next_if(|leaf| leaf.span.to.offset <= production.span.to.offset)
+        {
+            tokens.push(leaf);
+        }
+        nodes.push(Node::Production { production, tokens });
+    }
+    nodes.extend(leaves.map(Node::Token));
+
+    let errors = super::syntax_errors(lexer_errors, parser_errors);
+    (Cst { nodes, trailing }, errors)
+}
+
+impl Cst {
+    /// Every token of the grammar, in order.
+    pub fn leaves(&self) -> impl Iterator<Item = &Leaf> {
+        self.nodes.iter().flat_map(|node| match node {
+            Node::Production { tokens, .. } => tokens.iter(),
+            Node::Token(leaf) => std::slice::from_ref(leaf).iter(),
+        })
+    }
+}
+
+impl fmt::Display for Trivia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl fmt::Display for Leaf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for trivia in self.leading.iter() {
+            write!(f, "{}", trivia)?;
+        }
+        write!(f, "{}", self.text)?;
+        for trivia in self.trailing.iter() {
+            write!(f, "{}", trivia)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Cst {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for leaf in self.leaves() {
+            write!(f, "{}", leaf)?;
+        }
+        for trivia in self.trailing.iter() {
+            write!(f, "{}", trivia)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use super::{parse, Node, Trivia, TriviaKind};
+use crate::lexer::Token;
+use quickcheck_macros::quickcheck;
+
+fn trivia(kind: TriviaKind, text: &str) -> Trivia {
+    Trivia {
+        kind,
+        text: text.to_owned(),
+    }
+}
+
+#[test]
+fn test_lossless() {
+    let inputs = [
+        "",
+        "  \n",
+        "a = 'x';",
+        "(* header *)\r\na = 'x' , (* (* nested *) *) b ; (* trailing *)\n\nb = ? end  of input ?.\n",
+        "\tlong name = 2 * 'é', [ 'a' ] | (/ 'b' /) | (: 'c' :);\r",
+        "a = 'x' $ 'y'; # b = 'x'",
+        "a = (*) 'x';",
+        "a = 'x'; (* unterminated",
+        "a = 'unterminated",
+        "a 'x' ; b = 'y' ; ;",
+        "🦀 = 'x';",
+    ];
+    for input in inputs.iter() {
+        assert_eq!(parse(input).0.to_string(), *input);
+    }
+}
+
+#[quickcheck]
+fn test_arbitrary_input_is_lossless(input: String) -> bool {
+    parse(&input).0.to_string() == input
+}
+
+#[test]
+fn test_trivia() {
+    let (cst, errors) = parse("(* a *)\na = 'x', (* same line *)\n  b; (* last *)\n");
+    assert!(errors.is_empty());
+    let leaves = cst.leaves().collect::<Vec<_>>();
+    assert_eq!(leaves[0].token, Token::Nonterminal("a".to_owned()));
+    assert_eq!(
+        leaves[0].leading,
+        vec![
+            trivia(TriviaKind::Comment, "(* a *)"),
+            trivia(TriviaKind::Newline, "\n"),
+        ]
+    );
+    assert_eq!(leaves[3].text, ",");
+    assert_eq!(
+        leaves[3].trailing,
+        vec![
+            trivia(TriviaKind::Whitespace, " "),
+            trivia(TriviaKind::Comment, "(* same line *)"),
+        ]
+    );
+    assert_eq!(
+        leaves[4].leading,
+        vec![
+            trivia(TriviaKind::Newline, "\n"),
+            trivia(TriviaKind::Whitespace, "  "),
+        ]
+    );
+    assert_eq!(
+        leaves[5].trailing[1],
+        trivia(TriviaKind::Comment, "(* last *)")
+    );
+    assert_eq!(cst.trailing, vec![trivia(TriviaKind::Newline, "\n")]);
+    assert!(matches!(&cst.nodes[..], [Node::Production { tokens, .. }] if tokens.len() == 6));
+}
+
+#[test]
+fn test_skipped() {
+    let (cst, errors) = parse("a = 'x' $$ ;");
+    assert_eq!(errors.len(), 2);
+    let leaves = cst.leaves().collect::<Vec<_>>();
+    assert_eq!(
+        leaves[2].trailing,
+        vec![
+            trivia(TriviaKind::Whitespace, " "),
+            trivia(TriviaKind::Skipped, "$$"),
+            trivia(TriviaKind::Whitespace, " "),
+        ]
+    );
+}
@@ -0,0 +1,161 @@
+use super::span::{Span, Spanned};
+
+#[cfg(test)]
+mod tests;
+
+const RED: &str = "1;31";
+const YELLOW: &str = "1;33";
+const BLUE: &str = "1;34";
+const BOLD: &str = "1";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Error,
+    Warning,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+        }
+    }
+
+    fn style(self) -> &'static str {
+        match self {
+            Level::Error => RED,
+            Level::Warning => YELLOW,
+        }
+    }
+}
+
+/// An error or warning message with everything needed to point at its cause in the
+/// source of a grammar.
+pub struct Diagnostic<'a> {
+    pub level: Level,
+    pub message: String,
+    pub span: Span,
+    /// Other places in the source that explain the error.
+    pub labels: Vec<Spanned<String>>,
+    pub notes: Vec<String>,
+    pub help: Option<String>,
+    pub source: &'a str,
+}
+
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", style, text)
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Splits the source the same way the lexer counts lines.
+fn lines(source: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    let mut chars = source.char_indices().peekable();
+    while let Some((offset, ch)) = chars.next() {
+        match ch {
+            '\n' => {}
+            '\r' if matches!(chars.peek(), Some((_, '\n'))) => {
+                lines.push(&source[start..offset]);
+                chars.next();
+                start = offset + 2;
+                continue;
+            }
+            '\r' => {}
+            _ => continue,
+        }
+        lines.push(&source[start..offset]);
+        start = offset + 1;
+    }
+    lines.push(&source[start..]);
+    lines
+}
+
+/// The underline of `span` on `line`, keeping tabs so that it stays aligned
+/// with the text above it.
+fn underline(text: &str, line: usize, span: &Span, mark: char) -> String {
+    let length = text.chars().count();
+    let from = if span.from.line == line { span.from.column } else { 0 };
+    let to = if span.to.line == line { span.to.column } else { length };
+    let indent = text
+        .chars()
+        .take(from)
+        .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let width = to.saturating_sub(from).max(1);
+    format!("{}{}", indent, mark.to_string().repeat(width))
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Renders the diagnostic with the offending lines of the source and
+    /// every label underlined, optionally highlighted with ANSI escapes.
+    pub fn render(&self, color: bool) -> String {
+        let lines = lines(self.source);
+        let mut marks = vec![(self.span, None, '^', self.level.style())];
+        for label in self.labels.iter() {
+            marks.push((label.span, Some(label.node.as_str()), '-', BLUE));
+        }
+        marks.sort_by_key(|(span, ..)| span.from.line);
+        let last_line = marks.iter().map(|(span, ..)| span.from.line).max().unwrap_or(0);
+        let gutter = " ".repeat((last_line + 1).to_string().len());
+        let bar = paint("|", BLUE, color);
+
+        let mut output = String::new();
+        output.push_str(&format!(
+            "{}: {}\n",
+            paint(self.level.name(), self.level.style(), color),
+            paint(&self.message, BOLD, color)
+        ));
+        output.push_str(&format!(
+            "{}{} {}:{}\n",
+            gutter,
+            paint("-->", BLUE, color),
+            self.span.from.line + 1,
+            self.span.from.column + 1
+        ));
+        output.push_str(&format!("{} {}\n", gutter, bar));
+        let mut previous: Option<usize> = None;
+        for (span, label, mark, style) in marks.iter() {
+            let line = span.from.line;
+            let text = lines.get(line).copied().unwrap_or("");
+            if previous != Some(line) {
+                if matches!(previous, Some(previous) if previous + 1 < line) {
+                    output.push_str(&format!("{}\n", paint("...", BLUE, color)));
+                }
+                output.push_str(&format!(
+                    "{} {} {}\n",
+                    paint(&format!("{:>1$}", line + 1, gutter.len()), BLUE, color),
+                    bar,
+                    text
+                ));
+            }
+            previous = Some(line);
+            let underline = paint(&underline(text, line, span, *mark), style, color);
+            match label {
+                Some(label) => output.push_str(&format!(
+                    "{} {} {} {}\n",
+                    gutter,
+                    bar,
+                    underline,
+                    paint(label, style, color)
+                )),
+                None => output.push_str(&format!("{} {} {}\n", gutter, bar, underline)),
+            }
+        }
+        if !self.notes.is_empty() || self.help.is_some() {
+            output.push_str(&format!("{} {}\n", gutter, bar));
+        }
+        let equals = paint("=", BLUE, color);
+        for note in self.notes.iter() {
+            output.push_str(&format!("{} {} {}: {}\n", gutter, equals, paint("note", BOLD, color), note));
+        }
+        if let Some(help) = &self.help {
+            output.push_str(&format!("{} {} {}: {}\n", gutter, equals, paint("help", BOLD, color), help));
+        }
+        output
+    }
+}
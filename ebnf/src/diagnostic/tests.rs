@@ -0,0 +1,86 @@
+use crate::parse;
+
+#[test]
+fn test_render_undefined_rule() {
+    let source = "a = 'x';\nb = a, c;\n";
+    let errors = parse(source).unwrap_err();
+    assert_eq!(
+        errors[0].render(source, false),
+        "error: rule c is undefined\n \
+         --> 2:8\n  \
+          |\n\
+         2 | b = a, c;\n  \
+          |        ^\n  \
+          |\n  \
+          = help: define the rule, as in `c = ... ;`\n"
+    );
+}
+
+#[test]
+fn test_render_suggestion() {
+    let source = "expression = term, { '+', term };\nterm = 'n' | '(', expresion, ')';";
+    let errors = parse(source).unwrap_err();
+    assert_eq!(
+        errors[0].render(source, false),
+        "error: rule expresion is undefined\n \
+         --> 2:19\n  \
+          |\n\
+         2 | term = 'n' | '(', expresion, ')';\n  \
+          |                   ^^^^^^^^^\n  \
+          |\n  \
+          = help: a rule with a similar name exists: `expression`\n"
+    );
+}
+
+#[test]
+fn test_render_secondary_label() {
+    let source = "a = 'x';\nb = 'y';\n\ta = 'z';";
+    let errors = parse(source).unwrap_err();
+    assert_eq!(
+        errors[0].render(source, false),
+        "error: rule a is multiply defined\n \
+         --> 3:2\n  \
+          |\n\
+         1 | a = 'x';\n  \
+          | - first defined here\n\
+         ...\n\
+         3 | \ta = 'z';\n  \
+          | \t^\n  \
+          |\n  \
+          = help: merge the definitions of rule a into one, separated with `|`\n"
+    );
+}
+
+#[test]
+fn test_render_color() {
+    let source = "a = 'x'";
+    let errors = parse(source).unwrap_err();
+    let rendered = errors[0].render(source, true);
+    assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m: \x1b[1mexpected"));
+    assert!(rendered.contains("\x1b[1;31m    ^^^\x1b[0m"));
+}
+
+#[test]
+fn test_render_warning() {
+    let source = "a = 'x';\nb = 'y', b;\n";
+    let (_, warnings) = parse(source).unwrap();
+    assert_eq!(
+        warnings.iter().map(|warning| warning.render(source, false)).collect::<Vec<_>>(),
+        vec![
+            "warning: rule b is never used\n \
+             --> 2:1\n  \
+              |\n\
+             2 | b = 'y', b;\n  \
+              | ^\n  \
+              |\n  \
+              = help: remove rule b, or use it in another rule\n",
+            "warning: rule b never derives a finite string\n \
+             --> 2:1\n  \
+              |\n\
+             2 | b = 'y', b;\n  \
+              | ^\n  \
+              |\n  \
+              = help: add an alternative to rule b that doesn't use it, as in `a = 'x', a | 'x';`\n",
+        ]
+    );
+}
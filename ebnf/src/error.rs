@@ -1,3 +1,4 @@
+use super::diagnostic::{Diagnostic, Level};
 use super::span::{Span, Spanned, Spanning};
 use super::{lexer, parser, preprocessor};
 use crate::impl_spanning;
@@ -18,6 +19,14 @@ pub enum ErrorKind {
 
 impl_spanning!(Error);
 
+/// A replacement of the source at a span which resolves an error and can be
+/// applied without further edits.
+#[derive(Debug, PartialEq)]
+pub struct Fix {
+    pub span: Span,
+    pub replacement: String,
+}
+
 impl From<Spanned<lexer::error::Error>> for Error {
     fn from(error: Spanned<lexer::error::Error>) -> Error {
         Error {
@@ -45,6 +54,112 @@ impl From<Spanned<preprocessor::error::Error>> for Error {
     }
 }
 
+impl Error {
+    /// Other places in the grammar that explain the error.
+    pub fn labels(&self) -> Vec<Spanned<String>> {
+        match &self.kind {
+            ErrorKind::Preprocessor(preprocessor::error::Error::MultipleDefinitions(_, first)) => {
+                vec!["first defined here".to_owned().spanning(*first)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn notes(&self) -> Vec<String> {
+        use lexer::error::Error as Lexer;
+        use preprocessor::error::Error as Preprocessor;
+
+        match &self.kind {
+            ErrorKind::Lexer(Lexer::InvalidSymbol(symbol)) if symbol.len() == 3 => {
+                vec![format!("`{}` could start either a comment or a bracket pair", symbol)]
+            }
+            ErrorKind::Lexer(Lexer::EmptyTerminal) => {
+                vec!["a terminal must contain at least one character".to_owned()]
+            }
+            ErrorKind::Preprocessor(Preprocessor::LeftRecursion(_)) => {
+                vec!["a left-recursive rule can be checked only with the Earley engine".to_owned()]
+            }
+            ErrorKind::Preprocessor(Preprocessor::UnknownSpecial(_)) => vec![
+                "the built-in special sequences are `any character`, `letter`, `digit`, \
+                 `whitespace`, `end of input` and the Unicode general categories, such as `Lu`"
+                    .to_owned(),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        use lexer::error::Error as Lexer;
+        use preprocessor::error::Error as Preprocessor;
+
+        match &self.kind {
+            ErrorKind::Lexer(Lexer::InvalidSymbol(symbol)) if symbol.len() == 3 => Some(format!(
+                "separate the symbols with a space, as in `{} {}`",
+                &symbol[..1],
+                &symbol[1..]
+            )),
+            ErrorKind::Lexer(Lexer::UnterminatedSpecial) => {
+                Some("close the special sequence with `?`".to_owned())
+            }
+            ErrorKind::Lexer(Lexer::UnterminatedComment) => {
+                Some("close the comment with `*)`".to_owned())
+            }
+            ErrorKind::Lexer(Lexer::UnterminatedTerminal) => {
+                Some("close the terminal with the quote it starts with".to_owned())
+            }
+            ErrorKind::Lexer(Lexer::EmptyTerminal) => {
+                Some("leave the definition empty instead, as in `a = ;`".to_owned())
+            }
+            ErrorKind::Preprocessor(Preprocessor::UndefinedRule(_, Some(suggestion)))
+            | ErrorKind::Preprocessor(Preprocessor::UndefinedStart(_, Some(suggestion))) => {
+                Some(format!("a rule with a similar name exists: `{}`", suggestion))
+            }
+            ErrorKind::Preprocessor(Preprocessor::UndefinedRule(rule, None)) => {
+                Some(format!("define the rule, as in `{} = ... ;`", rule))
+            }
+            ErrorKind::Preprocessor(Preprocessor::LeftRecursion(chain)) => Some(format!(
+                "rewrite rule {} using repetition, as in `a = b, {{ c }}` instead of `a = a, c | b`",
+                chain.first().unwrap()
+            )),
+            ErrorKind::Preprocessor(Preprocessor::MultipleDefinitions(rule, _)) => Some(format!(
+                "merge the definitions of rule {} into one, separated with `|`",
+                rule
+            )),
+            _ => None,
+        }
+    }
+
+    /// The edit which resolves the error, if it can be made mechanically.
+    pub fn fix(&self) -> Option<Fix> {
+        use preprocessor::error::Error as Preprocessor;
+
+        match &self.kind {
+            ErrorKind::Preprocessor(Preprocessor::UndefinedRule(_, Some(suggestion))) => {
+                Some(Fix {
+                    span: self.span,
+                    replacement: suggestion.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Renders the error with the lines of `source` it points at, its labels,
+    /// notes and help, optionally highlighted with ANSI escapes.
+    pub fn render(&self, source: &str, color: bool) -> String {
+        Diagnostic {
+            level: Level::Error,
+            message: self.to_string(),
+            span: self.span,
+            labels: self.labels(),
+            notes: self.notes(),
+            help: self.help(),
+            source,
+        }
+        .render(color)
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.kind {
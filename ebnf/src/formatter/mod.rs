@@ -0,0 +1,303 @@
+use super::cst::{self, Leaf, Node, Trivia, TriviaKind};
+use super::error::Error;
+use super::lexer::Token;
+use super::parser::{Expression, Production};
+use super::span::Spanned;
+use base::printer::{enclose, group, join, terminate, Precedence};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Width in characters past which the alternatives of a production are
+    /// put on separate lines.
+    pub max_width: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { max_width: 80 }
+    }
+}
+
+fn precedence(expression: &Expression) -> Precedence {
+    match expression {
+        Expression::Alternative { .. } => Precedence::Alternative,
+        Expression::Sequence { .. } => Precedence::Sequence,
+        Expression::Exception { .. } => Precedence::Exception,
+        Expression::Factor { .. } => Precedence::Factor,
+        _ => Precedence::Primary,
+    }
+}
+
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// A line printed before a production.
+enum Line {
+    Blank,
+    Comment(String),
+}
+
+/// The comments on their own lines among `trivia`, and the blank lines
+/// between them, with runs of blank lines collapsed into one.
+fn lines(trivia: &[Trivia], first: bool) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut newlines = 0;
+    for trivia in trivia.iter() {
+        match trivia.kind {
+            TriviaKind::Newline => newlines += 1,
+            TriviaKind::Comment => {
+                if newlines >= 2 && !(first && lines.is_empty()) {
+                    lines.push(Line::Blank);
+                }
+                lines.push(Line::Comment(trivia.text.clone()));
+                newlines = 0;
+            }
+            TriviaKind::Whitespace | TriviaKind::Skipped => {}
+        }
+    }
+    if newlines >= 2 && !(first && lines.is_empty()) {
+        lines.push(Line::Blank);
+    }
+    lines
+}
+
+fn comments(trivia: &[Trivia]) -> impl Iterator<Item = &str> {
+    trivia
+        .iter()
+        .filter(|trivia| trivia.kind == TriviaKind::Comment)
+        .map(|trivia| trivia.text.as_str())
+}
+
+struct Formatter<'a> {
+    /// Tokens by their offset in the source, for the exact text of names,
+    /// terminals and special sequences.
+    leaves: HashMap<usize, &'a Leaf>,
+    options: &'a Options,
+}
+
+impl<'a> Formatter<'a> {
+    /// The exact text of a name, terminal or special sequence. A primary in
+    /// parentheses has the span of the group, so there's no such token there.
+    fn text(&self, expression: &Spanned<Expression>) -> Option<&'a str> {
+        self.leaves
+            .get(&expression.span.from.offset)
+            .filter(|leaf| leaf.span == expression.span)
+            .filter(|leaf| {
+                matches!(
+                    (&leaf.token, &expression.node),
+                    (Token::Nonterminal(_), Expression::Nonterminal(_))
+                        | (Token::Terminal(_), Expression::Terminal(_))
+                        | (Token::Special(_), Expression::Special(_))
+                )
+            })
+            .map(|leaf| leaf.text.as_str())
+    }
+
+    fn name(&self, name: &Spanned<String>) -> String {
+        match self.leaves.get(&name.span.from.offset) {
+            Some(leaf) => leaf.text.split_whitespace().collect::<Vec<_>>().join(" "),
+            None => name.node.clone(),
+        }
+    }
+
+    fn operand(&self, expression: &Spanned<Expression>, loosest: Precedence) -> String {
+        group(self.expression(expression), precedence(&expression.node), loosest)
+    }
+
+    fn alternatives(&self, expression: &Spanned<Expression>) -> Vec<String> {
+        match &expression.node {
+            Expression::Alternative {
+                first: box first,
+                second: box second,
+                rest,
+            } => [first, second]
+                .iter()
+                .copied()
+                .chain(rest.iter())
+                .map(|expression| self.operand(expression, Precedence::Sequence))
+                .collect(),
+            _ => vec![self.expression(expression)],
+        }
+    }
+
+    fn expression(&self, expression: &Spanned<Expression>) -> String {
+        match &expression.node {
+            Expression::Alternative { .. } => join(self.alternatives(expression), " ", "|"),
+            Expression::Sequence {
+                first: box first,
+                second: box second,
+                rest,
+            } => join(
+                [first, second]
+                    .iter()
+                    .copied()
+                    .chain(rest.iter())
+                    .map(|expression| self.operand(expression, Precedence::Exception)),
+                "",
+                ",",
+            ),
+            Expression::Optional(box inner) => enclose("[", &self.expression(inner), "]"),
+            Expression::Repeated(box inner) => enclose("{", &self.expression(inner), "}"),
+            Expression::Factor {
+                count,
+                primary: box primary,
+            } => join(
+                vec![
+                    count.node.to_string(),
+                    self.operand(primary, Precedence::Primary),
+                ],
+                " ",
+                "*",
+            ),
+            Expression::Exception {
+                subject: box subject,
+                restriction: box restriction,
+            } => join(
+                vec![
+                    self.operand(subject, Precedence::Factor),
+                    self.operand(restriction, Precedence::Factor),
+                ],
+                " ",
+                "-",
+            ),
+            Expression::Nonterminal(identifier) => match self.text(expression) {
+                Some(text) => text.split_whitespace().collect::<Vec<_>>().join(" "),
+                None => identifier.clone(),
+            },
+            Expression::Terminal(content) => match self.text(expression) {
+                Some(text) => text.to_owned(),
+                None if content.contains('\'') => format!("\"{}\"", content),
+                None => format!("'{}'", content),
+            },
+            Expression::Special(content) => match self.text(expression) {
+                Some(text) => text.to_owned(),
+                None => format!("?{}?", content),
+            },
+            Expression::Empty => String::new(),
+            Expression::Error => unreachable!(),
+        }
+    }
+
+    /// Prints a production with its name padded to `padding`, followed by
+    /// the comments from within it.
+    fn production(
+        &self,
+        Spanned {
+            node: Production { lhs, rhs },
+            ..
+        }: &Spanned<Production>,
+        padding: usize,
+        comments: Vec<&str>,
+    ) -> String {
+        let name = self.name(lhs);
+        let head = format!("{}{} =", name, " ".repeat(padding - width(&name)));
+        let mut comments = comments.join(" ");
+        if !comments.is_empty() {
+            comments.insert(0, ' ');
+        }
+        let body = self.expression(rhs);
+        let line = if body.is_empty() {
+            format!("{}{}", terminate(&head, ";"), comments)
+        } else {
+            format!("{}{}", terminate(&format!("{} {}", head, body), ";"), comments)
+        };
+        let alternatives = self.alternatives(rhs);
+        if width(&line) <= self.options.max_width || alternatives.len() < 2 {
+            return line;
+        }
+        let indent = " ".repeat(padding + 1);
+        let mut lines = Vec::new();
+        for (i, alternative) in alternatives.into_iter().enumerate() {
+            let separator = if i == 0 {
+                head.clone()
+            } else {
+                format!("{}|", indent)
+            };
+            if alternative.is_empty() {
+                lines.push(separator);
+            } else {
+                lines.push(format!("{} {}", separator, alternative));
+            }
+        }
+        format!("{}{}", terminate(&lines.join("\n"), ";"), comments)
+    }
+}
+
+fn push_lines(output: &mut String, lines: Vec<Line>) {
+    for line in lines {
+        match line {
+            Line::Blank => output.push('\n'),
+            Line::Comment(comment) => {
+                output.push_str(&comment);
+                output.push('\n');
+            }
+        }
+    }
+}
+
+/// Formats a grammar in the canonical style, aligning the `=` of
+/// consecutive productions and putting each alternative of a production too
+/// long for one line on a line of its own. Comments before a production stay
+/// on their own lines, while comments from within it are moved after its
+/// terminator.
+pub fn format(input: &str) -> Result<String, Vec<Error>> {
+    format_with(input, &Options::default())
+}
+
+pub fn format_with(input: &str, options: &Options) -> Result<String, Vec<Error>> {
+    let (cst, errors) = cst::parse(input);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let formatter = Formatter {
+        leaves: cst
+            .leaves()
+            .map(|leaf| (leaf.span.from.offset, leaf))
+            .collect(),
+        options,
+    };
+
+    let mut productions = Vec::new();
+    for (i, node) in cst.nodes.iter().enumerate() {
+        if let Node::Production { production, tokens } = node {
+            let (first, rest) = tokens.split_first().unwrap();
+            let comments = comments(&first.trailing)
+                .chain(
+                    rest.iter()
+                        .flat_map(|leaf| comments(&leaf.leading).chain(comments(&leaf.trailing))),
+                )
+                .collect::<Vec<_>>();
+            productions.push((lines(&first.leading, i == 0), production, comments));
+        }
+    }
+
+    // productions separated by a blank line are aligned separately
+    let mut blocks: Vec<Vec<_>> = Vec::new();
+    for production in productions {
+        let (lines, ..) = &production;
+        if blocks.is_empty() || lines.iter().any(|line| matches!(line, Line::Blank)) {
+            blocks.push(Vec::new());
+        }
+        blocks.last_mut().unwrap().push(production);
+    }
+    let mut output = String::new();
+    for block in blocks {
+        let padding = block
+            .iter()
+            .map(|(_, production, _)| width(&formatter.name(&production.node.lhs)))
+            .max()
+            .unwrap_or(0);
+        for (lines, production, comments) in block {
+            push_lines(&mut output, lines);
+            output.push_str(&formatter.production(production, padding, comments));
+            output.push('\n');
+        }
+    }
+    push_lines(&mut output, lines(&cst.trailing, cst.nodes.is_empty()));
+    Ok(output)
+}
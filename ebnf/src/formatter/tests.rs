@@ -0,0 +1,104 @@
+use super::{format, format_with, Options};
+use crate::parse;
+
+#[test]
+fn test_alignment_and_spacing() {
+    let input = "expression=term,{('+'|'-'),term};\nterm = factor , { ( '*' / '/' ) , factor } .\n\
+                 factor = constant|variable|'(',expression,')';\n";
+    assert_eq!(
+        format(input).unwrap(),
+        "expression = term, { ('+' | '-'), term };\n\
+         term       = factor, { ('*' | '/'), factor };\n\
+         factor     = constant | variable | '(', expression, ')';\n"
+    );
+}
+
+#[test]
+fn test_grouping() {
+    assert_eq!(
+        format("a = ((b | c)), (d, e) - f, 2 * (g, h), 3 * [i], (j - k) - l, m - (n - o);")
+            .unwrap(),
+        "a = (b | c), (d, e) - f, 2 * (g, h), 3 * [ i ], (j - k) - l, m - (n - o);\n"
+    );
+    assert_eq!(
+        format("a = ( b ), 'x'; b = ( 'y' ) - 'z';").unwrap(),
+        "a = b, 'x';\nb = 'y' - 'z';\n"
+    );
+    assert_eq!(
+        format("a = ; b = | 'x', ;").unwrap(),
+        "a = ;\nb = | 'x', ;\n"
+    );
+}
+
+#[test]
+fn test_empty_operands() {
+    assert_eq!(
+        format("a = [ ], {}, ( 'x', ), [ 'y' | ];").unwrap(),
+        "a = [], {}, ('x', ), [ 'y' | ];\n"
+    );
+    assert_eq!(format("a = 'x' |;").unwrap(), "a = 'x' | ;\n");
+    assert_eq!(
+        format_with("a = 'x' | 'y' | ;", &Options { max_width: 10 }).unwrap(),
+        "a = 'x'\n  | 'y'\n  | ;\n"
+    );
+}
+
+#[test]
+fn test_comments_and_blank_lines() {
+    let input =
+        "\n\n(* header *)\n\n\n(* about a *)\na = 'x' (* inner *) | b; (* after *)\nbb = 'y';\n\
+                 \n\n\nlonger = 'z';\n(* footer *)\n";
+    assert_eq!(
+        format(input).unwrap(),
+        "(* header *)\n\
+         \n\
+         (* about a *)\n\
+         a  = 'x' | b; (* inner *) (* after *)\n\
+         bb = 'y';\n\
+         \n\
+         longer = 'z';\n\
+         (* footer *)\n"
+    );
+}
+
+#[test]
+fn test_wrapping() {
+    let options = Options { max_width: 40 };
+    assert_eq!(
+        format_with("digit = '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7';", &options).unwrap(),
+        "digit = '0'\n      | '1'\n      | '2'\n      | '3'\n      | '4'\n      | '5'\n      | '6'\n      | '7';\n"
+    );
+    assert_eq!(
+        format_with(
+            "long = 'a long terminal', 'another long terminal';",
+            &options
+        )
+        .unwrap(),
+        "long = 'a long terminal', 'another long terminal';\n"
+    );
+}
+
+#[test]
+fn test_idempotent_and_equivalent() {
+    let inputs = [
+        "a = { [ 'x' ] | ? letter ? } - \"'\" ; (* c *) b=a,a.",
+        "long rule name = 'a' | 'b', 'c' | (* x *) 'd' | 3 * 'e' | 'f' | 'g' | 'h' | 'i' | 'j';",
+        "(* a *) a = 'x' ; (* b *)\n\n\n(* c *)",
+        "a = [ ], { , }, ( 'x' | ), 2 * , 'y' - ;",
+    ];
+    for input in inputs.iter() {
+        let formatted = format(input).unwrap();
+        assert_eq!(format(&formatted).unwrap(), formatted);
+        assert_eq!(
+            parse(&formatted).ok().map(|(grammar, _)| grammar),
+            parse(input).ok().map(|(grammar, _)| grammar)
+        );
+    }
+}
+
+#[test]
+fn test_syntax_errors() {
+    assert!(format("a = 'x' $ ;").is_err());
+    assert!(format("a = 'x'").is_err());
+    assert!(format("(* no productions *)").is_err());
+}
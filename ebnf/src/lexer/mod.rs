@@ -37,11 +37,14 @@ fn scan<'a>(string: &'a str) -> Result<Vec<Spanned<&'a str>>, Spanned<Error>> {
         .graphemes(true)
         .scan(Location::new(), |location, grapheme| {
             let current_location = *location;
-            (*location).column += grapheme.chars().count();
+            location.column += grapheme.chars().count();
+            location.offset += grapheme.len();
+            location.utf16_column += grapheme.encode_utf16().count();
             match grapheme {
                 "\n" | "\r" | "\r\n" => {
-                    (*location).line += 1;
-                    (*location).column = 0;
+                    location.line += 1;
+                    location.column = 0;
+                    location.utf16_column = 0;
                 }
                 _ => {}
             }
@@ -57,10 +60,17 @@ fn scan<'a>(string: &'a str) -> Result<Vec<Spanned<&'a str>>, Spanned<Error>> {
         .collect::<Result<Vec<Spanned<&'a str>>, Spanned<Error>>>()
 }
 
-pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
-    let symbols = scan(string)?;
+/// Tokenizes the whole input, skipping over invalid symbols instead of
+/// stopping at the first one. Returns the tokens that could be read along
+/// with every error.
+pub fn lex(string: &str) -> (Vec<Spanned<Token>>, Vec<Spanned<Error>>) {
+    let symbols = match scan(string) {
+        Ok(symbols) => symbols,
+        Err(error) => return (Vec::new(), vec![error]),
+    };
 
     let mut tokens = Vec::new();
+    let mut errors = Vec::new();
     let mut i = 0;
 
     'tokens: loop {
@@ -79,8 +89,10 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                             span: oe,
                         }) = symbols.get(i + 2)
                         {
-                            return Err(Error::InvalidSymbol("(*)".to_owned())
+                            errors.push(Error::InvalidSymbol("(*)".to_owned())
                                 .spanning(Span::combine(os, oe)));
+                            i += 3;
+                            continue 'gap;
                         }
                         i += 2;
                         let mut nest_level = 1;
@@ -97,8 +109,10 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                                             span: oe,
                                         }) = symbols.get(i + 2)
                                         {
-                                            return Err(Error::InvalidSymbol("(*)".to_owned())
+                                            errors.push(Error::InvalidSymbol("(*)".to_owned())
                                                 .spanning(Span::combine(os, oe)));
+                                            i += 3;
+                                            continue;
                                         }
                                         i += 2;
                                         nest_level += 1;
@@ -120,8 +134,9 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                                     i += 1;
                                 }
                                 None => {
-                                    return Err(Error::UnterminatedComment
-                                        .spanning(symbols.get(i - 1).unwrap().span))
+                                    errors.push(Error::UnterminatedComment
+                                        .spanning(symbols.get(i - 1).unwrap().span));
+                                    break;
                                 }
                             };
                         }
@@ -184,7 +199,8 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                     i += 2;
                 }
                 _ => {
-                    return Err(Error::InvalidSymbol(':'.to_string()).spanning(*start));
+                    errors.push(Error::InvalidSymbol(':'.to_string()).spanning(*start));
+                    i += 1;
                 }
             },
             Some(Spanned { node: "-", span }) => {
@@ -207,8 +223,9 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                         node: ")",
                         span: end,
                     }) => {
-                        return Err(Error::InvalidSymbol("(/)".to_owned())
+                        errors.push(Error::InvalidSymbol("(/)".to_owned())
                             .spanning(Span::combine(start, end)));
+                        i += 3;
                     }
                     _ => {
                         tokens.push(Token::StartOption.spanning(Span::combine(start, middle)));
@@ -223,8 +240,9 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                         node: ")",
                         span: end,
                     }) => {
-                        return Err(Error::InvalidSymbol("(:)".to_owned())
+                        errors.push(Error::InvalidSymbol("(:)".to_owned())
                             .spanning(Span::combine(start, end)));
+                        i += 3;
                     }
                     _ => {
                         tokens.push(Token::StartRepeat.spanning(Span::combine(start, middle)));
@@ -259,13 +277,13 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                     match symbols.get(i) {
                         Some(Spanned { node: c, span: oe }) if c == quote => {
                             if len == 0 {
-                                return Err(Error::EmptyTerminal.spanning(Span::combine(os, oe)));
-                            } else {
-                                tokens
-                                    .push(Token::Terminal(string).spanning(Span::combine(os, oe)));
-                                i += 1;
-                                break 'terminal;
+                                errors.push(Error::EmptyTerminal.spanning(Span::combine(os, oe)));
                             }
+                            // an empty terminal still stands in for one, so that
+                            // it causes no further errors in the parser
+                            tokens.push(Token::Terminal(string).spanning(Span::combine(os, oe)));
+                            i += 1;
+                            break 'terminal;
                         }
                         Some(Spanned { node: c, .. }) => {
                             string.push_str(c);
@@ -273,8 +291,9 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                             len += 1;
                         }
                         None => {
-                            return Err(Error::UnterminatedTerminal
-                                .spanning(symbols.get(i - 1).unwrap().span))
+                            errors.push(Error::UnterminatedTerminal
+                                .spanning(symbols.get(i - 1).unwrap().span));
+                            break 'terminal;
                         }
                     }
                 }
@@ -300,8 +319,9 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                             i += 1;
                         }
                         None => {
-                            return Err(Error::UnterminatedSpecial
-                                .spanning(symbols.get(i - 1).unwrap().span))
+                            errors.push(Error::UnterminatedSpecial
+                                .spanning(symbols.get(i - 1).unwrap().span));
+                            break 'special;
                         }
                     }
                 }
@@ -350,11 +370,12 @@ pub(super) fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Spanned<Error>> {
                 }
             }
             Some(Spanned { node: c, span }) => {
-                return Err(Error::InvalidSymbol((*c).to_string()).spanning(*span));
+                errors.push(Error::InvalidSymbol((*c).to_string()).spanning(*span));
+                i += 1;
             }
             None => break 'tokens,
         }
     }
 
-    Ok(tokens)
+    (tokens, errors)
 }
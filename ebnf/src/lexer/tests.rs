@@ -1,6 +1,34 @@
-use super::{lex, scan, Error, Span, Spanning, Token};
+use super::{Error, Location, Span, Spanned, Spanning, Token};
 use quickcheck_macros::quickcheck;
 
+/// Keeps only the lines and columns of a span, which is what the tests spell
+/// out.
+fn columns<T>(Spanned { node, span }: Spanned<T>) -> Spanned<T> {
+    let location = |location: Location| Location {
+        column: location.column,
+        line: location.line,
+        ..Location::new()
+    };
+    Spanned {
+        node,
+        span: Span {
+            from: location(span.from),
+            to: location(span.to),
+        },
+    }
+}
+
+fn scan(string: &str) -> Result<Vec<Spanned<&str>>, Spanned<Error>> {
+    super::scan(string).map(|symbols| symbols.into_iter().map(columns).collect())
+}
+
+fn lex(string: &str) -> Result<Vec<Spanned<Token>>, Vec<Spanned<Error>>> {
+    match super::lex(string) {
+        (tokens, errors) if errors.is_empty() => Ok(tokens.into_iter().map(columns).collect()),
+        (_, errors) => Err(errors.into_iter().map(columns).collect()),
+    }
+}
+
 #[test]
 fn test_scan_control_characters() {
     use std::str;
@@ -55,6 +83,42 @@ fn test_scan_multiple_unicode_code_points() {
     );
 }
 
+#[test]
+fn test_scan_offsets() {
+    let location = |column, line, offset, utf16_column| Location {
+        column,
+        line,
+        offset,
+        utf16_column,
+    };
+    let spans = super::scan("e\u{301}🦀\r\nx")
+        .unwrap()
+        .into_iter()
+        .map(|symbol| symbol.span)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        spans,
+        vec![
+            Span {
+                from: location(0, 0, 0, 0),
+                to: location(2, 0, 3, 2),
+            },
+            Span {
+                from: location(2, 0, 3, 2),
+                to: location(3, 0, 7, 4),
+            },
+            Span {
+                from: location(3, 0, 7, 4),
+                to: location(0, 1, 9, 0),
+            },
+            Span {
+                from: location(0, 1, 9, 0),
+                to: location(1, 1, 10, 1),
+            },
+        ]
+    );
+}
+
 #[test]
 fn test_concatenation() {
     assert_eq!(
@@ -103,7 +167,7 @@ fn test_options() {
     );
     assert_eq!(
         lex(" (/) "),
-        Err(Error::InvalidSymbol("(/)".to_owned()).spanning(Span::from(((1, 0), (4, 0)))))
+        Err(vec![Error::InvalidSymbol("(/)".to_owned()).spanning(Span::from(((1, 0), (4, 0))))])
     );
     assert_eq!(
         lex(" /"),
@@ -126,7 +190,7 @@ fn test_repeats() {
     );
     assert_eq!(
         lex(" (:) "),
-        Err(Error::InvalidSymbol("(:)".to_owned()).spanning(Span::from(((1, 0), (4, 0)))))
+        Err(vec![Error::InvalidSymbol("(:)".to_owned()).spanning(Span::from(((1, 0), (4, 0))))])
     );
 }
 
@@ -146,19 +210,19 @@ fn test_terminals() {
     );
     assert_eq!(
         lex(" ' a \""),
-        Err(Error::UnterminatedTerminal.spanning(Span::from(((5, 0), (6, 0)))))
+        Err(vec![Error::UnterminatedTerminal.spanning(Span::from(((5, 0), (6, 0))))])
     );
     assert_eq!(
         lex("\"bbb'   "),
-        Err(Error::UnterminatedTerminal.spanning(Span::from(((7, 0), (8, 0)))))
+        Err(vec![Error::UnterminatedTerminal.spanning(Span::from(((7, 0), (8, 0))))])
     );
     assert_eq!(
         lex("\"\""),
-        Err(Error::EmptyTerminal.spanning(Span::from(((0, 0), (2, 0)))))
+        Err(vec![Error::EmptyTerminal.spanning(Span::from(((0, 0), (2, 0))))])
     );
     assert_eq!(
         lex("''"),
-        Err(Error::EmptyTerminal.spanning(Span::from(((0, 0), (2, 0)))))
+        Err(vec![Error::EmptyTerminal.spanning(Span::from(((0, 0), (2, 0))))])
     );
     //     ok_case!(
     //         terminal,
@@ -205,7 +269,7 @@ fn test_specials() {
     );
     assert_eq!(
         lex(" ?bbb  "),
-        Err(Error::UnterminatedSpecial.spanning(Span::from(((6, 0), (7, 0)))))
+        Err(vec![Error::UnterminatedSpecial.spanning(Span::from(((6, 0), (7, 0))))])
     );
     assert_eq!(
         lex("??"),
@@ -333,7 +397,7 @@ fn test_nonterminals() {
 fn test_invalid_symbols() {
     assert_eq!(
         lex(" + "),
-        Err(Error::InvalidSymbol('+'.to_string()).spanning(Span::from(((1, 0), (2, 0)))))
+        Err(vec![Error::InvalidSymbol('+'.to_string()).spanning(Span::from(((1, 0), (2, 0))))])
     );
 }
 
@@ -353,11 +417,11 @@ fn test_comments() {
     assert_eq!(lex(" (* test *) "), Ok(vec![]));
     assert_eq!(
         lex(" (* test * "),
-        Err(Error::UnterminatedComment.spanning(Span::from(((10, 0), (11, 0)))))
+        Err(vec![Error::UnterminatedComment.spanning(Span::from(((10, 0), (11, 0))))])
     );
     assert_eq!(
         lex(" (* ("),
-        Err(Error::UnterminatedComment.spanning(Span::from(((4, 0), (5, 0)))))
+        Err(vec![Error::UnterminatedComment.spanning(Span::from(((4, 0), (5, 0))))])
     );
     assert_eq!(
         lex(", (*, *) , "),
@@ -376,11 +440,11 @@ fn test_comments() {
     );
     assert_eq!(
         lex(" (* (* *) "),
-        Err(Error::UnterminatedComment.spanning(Span::from(((9, 0), (10, 0)))))
+        Err(vec![Error::UnterminatedComment.spanning(Span::from(((9, 0), (10, 0))))])
     );
     assert_eq!(
         lex(" (*) "),
-        Err(Error::InvalidSymbol("(*)".to_owned()).spanning(Span::from(((1, 0), (4, 0)))))
+        Err(vec![Error::InvalidSymbol("(*)".to_owned()).spanning(Span::from(((1, 0), (4, 0))))])
     );
 }
 
@@ -416,3 +480,26 @@ fn test_multiple_unicode_code_points() {
 fn test_arbitrary_input(input: String) {
     let _ = lex(&input);
 }
+
+#[test]
+fn test_recovery() {
+    let (tokens, errors) = super::lex("a + '' (*) b");
+    assert_eq!(
+        (
+            tokens.into_iter().map(columns).collect::<Vec<_>>(),
+            errors.into_iter().map(columns).collect::<Vec<_>>()
+        ),
+        (
+            vec![
+                Token::Nonterminal("a".to_owned()).spanning(Span::from(((0, 0), (1, 0)))),
+                Token::Terminal("".to_owned()).spanning(Span::from(((4, 0), (6, 0)))),
+                Token::Nonterminal("b".to_owned()).spanning(Span::from(((11, 0), (12, 0)))),
+            ],
+            vec![
+                Error::InvalidSymbol('+'.to_string()).spanning(Span::from(((2, 0), (3, 0)))),
+                Error::EmptyTerminal.spanning(Span::from(((4, 0), (6, 0)))),
+                Error::InvalidSymbol("(*)".to_owned()).spanning(Span::from(((7, 0), (10, 0)))),
+            ]
+        )
+    );
+}
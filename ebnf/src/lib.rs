@@ -1,18 +1,95 @@
 #![feature(box_syntax, box_patterns, bindings_after_at)]
+// spans make up most of every error, and are kept inline on purpose
+#![allow(clippy::result_large_err)]
 
-mod compiler;
+//! A grammar is read in stages, each of which can be run on its own:
+//! [`lexer::lex`] splits the source into tokens, [`parser::parse`] builds a
+//! spanned syntax tree out of them, [`preprocessor::preprocess`] validates it
+//! and [`compiler::compile`] turns it into a [`base::Grammar`]. [`parse`] runs
+//! all of them and collects their errors, while [`cst::parse`] keeps the
+//! comments and whitespace the other stages drop.
+
+pub mod compiler;
+pub mod cst;
+mod diagnostic;
 pub mod error;
-mod lexer;
-mod parser;
-mod preprocessor;
-mod span;
+pub mod formatter;
+pub mod lexer;
+pub mod parser;
+pub mod preprocessor;
+pub mod span;
+pub mod warning;
 
 use error::Error;
+use warning::Warning;
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Accept left-recursive rules, for grammars checked with an engine that
+    /// supports them.
+    pub allow_left_recursion: bool,
+    /// Special sequences the grammar may use besides the built-in ones.
+    pub specials: base::SpecialRegistry,
+    /// The rule the grammar is meant to be checked from, which every other
+    /// rule should be reachable from. The first rule of the grammar if unset.
+    pub start: Option<String>,
+}
+
+/// Lexer and parser errors, in the order they appear.
+fn syntax_errors(
+    lexer_errors: Vec<span::Spanned<lexer::error::Error>>,
+    parser_errors: Vec<span::Spanned<parser::error::Error>>,
+) -> Vec<Error> {
+    let mut errors = lexer_errors
+        .into_iter()
+        .map(Error::from)
+        .chain(parser_errors.into_iter().map(Error::from))
+        .collect::<Vec<_>>();
+    errors.sort_by_key(|error| (error.span.from.line, error.span.from.column));
+    errors
+}
+
+pub fn parse(input: &str) -> Result<(base::Grammar, Vec<Warning>), Vec<Error>> {
+    parse_with(input, &Options::default())
+}
+
+/// Parses a grammar, reporting every error found rather than only the first.
+/// Syntax errors are reported in the order they appear, and the grammar is
+/// only validated once it is free of them. A valid grammar comes with
+/// warnings about the rules that are likely mistakes.
+pub fn parse_with(
+    input: &str,
+    options: &Options,
+) -> Result<(base::Grammar, Vec<Warning>), Vec<Error>> {
+    let (tokens, lexer_errors) = lexer::lex(input);
+    let (ast, parser_errors) = parser::parse(&tokens);
+    let errors = syntax_errors(lexer_errors, parser_errors);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    match preprocessor::preprocess(ast, options) {
+        Ok(ast) => {
+            let warnings = preprocessor::warn(&ast, options);
+            let warnings = warnings.into_iter().map(Warning::from).collect();
+            let mut grammar = compiler::compile_with_source(ast, input);
+            if let Some(start) = &options.start {
+                grammar.set_start(start);
+            }
+            Ok((grammar, warnings))
+        }
+        Err(errors) => Err(errors.into_iter().map(Error::from).collect()),
+    }
+}
 
-pub fn parse(input: &str) -> Result<base::Grammar, Error> {
-    let tokens = lexer::lex(input)?;
-    let ast = parser::parse(&tokens)?;
-    let ast = preprocessor::preprocess(ast)?;
-    let ast = compiler::compile(ast);
-    Ok(ast)
+/// Names of the rules whose productions are well-formed, even if other parts
+/// of the grammar are not.
+pub fn rule_names(input: &str) -> Vec<String> {
+    let (tokens, _) = lexer::lex(input);
+    let (ast, _) = parser::parse(&tokens);
+    ast.node
+        .productions
+        .into_iter()
+        .filter(|production| production.node.rhs.node != parser::Expression::Error)
+        .map(|production| production.node.lhs.node)
+        .collect()
 }
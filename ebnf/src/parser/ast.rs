@@ -38,6 +38,9 @@ pub enum Expression {
     Terminal(String),
     Special(String),
     Empty,
+    /// Stands in for the right-hand side of a production that failed to
+    /// parse.
+    Error,
 }
 
 impl_spanning!(Grammar);
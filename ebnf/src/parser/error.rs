@@ -1,5 +1,5 @@
 use super::Tokens;
-use super::{Span, Spanned, Spanning};
+use super::{Span, Spanned, Spanning, Token};
 use crate::impl_spanning;
 use nom::InputIter;
 use std::fmt;
@@ -24,9 +24,59 @@ pub enum Error {
     StartOptionSymbolExpected,
     StartRepeatSymbolExpected,
     TerminatorSymbolExpected,
+    Unexpected {
+        expected: Vec<Expected>,
+        found: Option<Token>,
+    },
     Nom(nom::error::ErrorKind),
 }
 
+/// A token that a parser would have accepted.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Expected {
+    Identifier,
+    Nonterminal,
+    Terminal,
+    Special,
+    Integer,
+    Concatenation,
+    Definition,
+    DefinitionSeparator,
+    EndGroup,
+    EndOption,
+    EndRepeat,
+    Exception,
+    Repetition,
+    StartGroup,
+    StartOption,
+    StartRepeat,
+    Terminator,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Identifier => write!(f, "identifier"),
+            Expected::Nonterminal => write!(f, "nonterminal"),
+            Expected::Terminal => write!(f, "terminal"),
+            Expected::Special => write!(f, "special sequence"),
+            Expected::Integer => write!(f, "integer"),
+            Expected::Concatenation => write!(f, "`,`"),
+            Expected::Definition => write!(f, "`=`"),
+            Expected::DefinitionSeparator => write!(f, "`|`"),
+            Expected::EndGroup => write!(f, "`)`"),
+            Expected::EndOption => write!(f, "`]`"),
+            Expected::EndRepeat => write!(f, "`}}`"),
+            Expected::Exception => write!(f, "`-`"),
+            Expected::Repetition => write!(f, "`*`"),
+            Expected::StartGroup => write!(f, "`(`"),
+            Expected::StartOption => write!(f, "`[`"),
+            Expected::StartRepeat => write!(f, "`{{`"),
+            Expected::Terminator => write!(f, "`;`"),
+        }
+    }
+}
+
 impl_spanning!(Error);
 
 impl<'a> nom::error::ParseError<Tokens<'a>> for Spanned<Error> {
@@ -66,6 +116,18 @@ impl fmt::Display for Error {
             Error::StartOptionSymbolExpected => write!(f, "start option symbol expected"),
             Error::StartRepeatSymbolExpected => write!(f, "start repeat symbol expected"),
             Error::TerminatorSymbolExpected => write!(f, "terminator symbol expected"),
+            Error::Unexpected { expected, found } => {
+                let expected = expected.iter().map(ToString::to_string).collect::<Vec<_>>();
+                match expected.split_last() {
+                    Some((last, [])) => write!(f, "expected {}", last)?,
+                    Some((last, init)) => write!(f, "expected {} or {}", init.join(", "), last)?,
+                    None => write!(f, "unexpected token")?,
+                }
+                match found {
+                    Some(token) => write!(f, " but found {}", token),
+                    None => write!(f, " but found end of input"),
+                }
+            }
             Error::Nom(_) => write!(f, "internal error"),
         }
     }
@@ -1,13 +1,12 @@
 use super::lexer::Token;
 use super::span::{Span, Spanned, Spanning};
 pub use ast::{Expression, Grammar, Production};
-use error::Error;
+use error::{Error, Expected};
 use nom::{
     branch::alt,
     combinator::{cut, map, opt},
-    multi::many1,
     sequence::{pair, preceded, separated_pair, terminated, tuple},
-    IResult,
+    IResult, InputLength,
 };
 use tokens::*;
 use utils::*;
@@ -18,6 +17,7 @@ pub mod error;
 mod tests;
 mod tokens;
 mod utils;
+pub mod visit;
 
 fn grouped(i: Tokens) -> IResult<Tokens, Spanned<Expression>, Spanned<Error>> {
     map(
@@ -149,21 +149,90 @@ fn production(i: Tokens) -> IResult<Tokens, Spanned<Production>, Spanned<Error>>
     )(i)
 }
 
-fn syntax(i: Tokens) -> IResult<Tokens, Spanned<Grammar>, Spanned<Error>> {
-    map(many1(production), |productions| {
-        let span = Span::combine(
-            &productions[0].span,
-            &productions[productions.len() - 1].span,
-        );
-        Grammar { productions }.spanning(span)
-    })(i)
+/// Number of tokens to skip after a malformed production, up to and including
+/// its terminator, or up to the next `identifier =`, whichever comes first.
+fn resynchronize(tokens: &[Spanned<Token>]) -> usize {
+    for (index, token) in tokens.iter().enumerate() {
+        match (&token.node, tokens.get(index + 1)) {
+            (Token::Terminator, _) => return index + 1,
+            (
+                Token::Nonterminal(_),
+                Some(Spanned {
+                    node: Token::Definition,
+                    ..
+                }),
+            ) if index > 0 => return index,
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// Stands in for a malformed production, so that its name is still defined.
+/// Returns `None` if the production does not even start with `identifier =`.
+fn error_production(skipped: &[Spanned<Token>]) -> Option<Spanned<Production>> {
+    match skipped {
+        [Spanned {
+            node: Token::Nonterminal(identifier),
+            span: lhs,
+        }, Spanned {
+            node: Token::Definition,
+            span: definition,
+        }, rest @ ..] => {
+            let rhs = match rest {
+                [] => *definition,
+                [first, .., last] => Span::combine(&first.span, &last.span),
+                [only] => only.span,
+            };
+            Some(
+                Production {
+                    lhs: identifier.clone().spanning(*lhs),
+                    rhs: Expression::Error.spanning(rhs),
+                }
+                .spanning(Span::combine(lhs, &rhs)),
+            )
+        }
+        _ => None,
+    }
 }
 
-pub(super) fn parse(tokens: &[Spanned<Token>]) -> Result<Spanned<Grammar>, Spanned<Error>> {
-    match syntax(Tokens::new(&tokens)) {
-        Ok((_, grammar)) => Ok(grammar),
-        Err(nom::Err::Failure(inner)) => Err(inner),
-        Err(nom::Err::Error(inner)) => Err(inner),
-        _ => unreachable!(),
+/// Parses every production, recovering from malformed ones. The grammar
+/// contains an `Expression::Error` in place of the right-hand side of each
+/// production that failed to parse.
+pub fn parse(tokens: &[Spanned<Token>]) -> (Spanned<Grammar>, Vec<Spanned<Error>>) {
+    let mut position = 0;
+    let mut productions = Vec::new();
+    let mut errors = Vec::new();
+    loop {
+        let i = Tokens::new(&tokens[position..]);
+        match production(i.clone()) {
+            Ok((rest, production)) => {
+                productions.push(production);
+                position = tokens.len() - rest.input_len();
+            }
+            Err(nom::Err::Failure(inner)) | Err(nom::Err::Error(inner)) => {
+                // report every token that would have been accepted where parsing
+                // got furthest, rather than only the last one tried
+                let inner = i.furthest_error().unwrap_or(inner);
+                if position == tokens.len() {
+                    if productions.is_empty() && errors.is_empty() {
+                        errors.push(inner);
+                    }
+                    break;
+                }
+                errors.push(inner);
+                let skipped = &tokens[position..];
+                let skipped = &skipped[..resynchronize(skipped)];
+                productions.extend(error_production(skipped));
+                position += skipped.len();
+            }
+            _ => unreachable!(),
+        }
     }
+    let span = match productions.as_slice() {
+        [] => Span::new(),
+        [first, .., last] => Span::combine(&first.span, &last.span),
+        [only] => only.span,
+    };
+    (Grammar { productions }.spanning(span), errors)
 }
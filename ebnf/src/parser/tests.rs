@@ -1,5 +1,5 @@
 use super::super::span::Location;
-use super::{Error, Expression, Grammar, Production, Span, Spanned, Spanning, Token, Tokens};
+use super::{Error, Expected, Expression, Grammar, Production, Span, Spanned, Spanning, Token, Tokens};
 use nom::{Err, Slice};
 use quickcheck_macros::quickcheck;
 
@@ -432,16 +432,24 @@ fn test_productions() {
 
 #[test]
 fn test_syntaxes() {
-    use super::syntax;
+    use super::parse;
 
-    error_case!(
-        syntax,
-        &vec![],
-        Error::IdentifierExpected.spanning(Span::from(((0, 0), (0, 0))))
+    assert_eq!(
+        parse(&[]),
+        (
+            Grammar {
+                productions: vec![]
+            }
+            .spanning(Span::from(((0, 0), (0, 0)))),
+            vec![Error::Unexpected {
+                expected: vec![Expected::Identifier],
+                found: None,
+            }
+            .spanning(Span::from(((0, 0), (0, 0))))]
+        )
     );
-    ok_case!(
-        syntax,
-        &vec![
+    assert_eq!(
+        parse(&[
             Token::Nonterminal("a".to_owned()).spanning(Span::from(((0, 0), (1, 0)))),
             Token::Definition.spanning(Span::from(((2, 0), (3, 0)))),
             Token::Terminal("d".to_owned()).spanning(Span::from(((4, 0), (6, 0)))),
@@ -462,9 +470,9 @@ fn test_syntaxes() {
             Token::Terminal("c".to_owned()).spanning(Span::from(((47, 0), (50, 0)))),
             Token::EndGroup.spanning(Span::from(((50, 0), (51, 0)))),
             Token::Terminator.spanning(Span::from(((51, 0), (52, 0)))),
-        ],
-        20,
-        Grammar {
+        ]),
+        (
+            Grammar {
             productions: vec![
                 Production {
                     lhs: "a".to_owned().spanning(Span::from(((0, 0), (1, 0)))),
@@ -518,9 +526,93 @@ fn test_syntaxes() {
                 }
                 .spanning(Span::from(((33, 0), (52, 0))))
             ]
-        }
-        .spanning(Span::from(((0, 0), (52, 0))))
+            }
+            .spanning(Span::from(((0, 0), (52, 0)))),
+            vec![]
+        )
+    );
+}
+
+#[test]
+fn test_recovery() {
+    use super::parse;
+
+    // a = ; b = 'x' 'y' c = ] ; d = 'z';
+    let (grammar, errors) = parse(&[
+        Token::Nonterminal("a".to_owned()).spanning(Span::from(((0, 0), (1, 0)))),
+        Token::Definition.spanning(Span::from(((2, 0), (3, 0)))),
+        Token::Terminator.spanning(Span::from(((4, 0), (5, 0)))),
+        Token::Nonterminal("b".to_owned()).spanning(Span::from(((6, 0), (7, 0)))),
+        Token::Definition.spanning(Span::from(((8, 0), (9, 0)))),
+        Token::Terminal("x".to_owned()).spanning(Span::from(((10, 0), (13, 0)))),
+        Token::Terminal("y".to_owned()).spanning(Span::from(((14, 0), (17, 0)))),
+        Token::Nonterminal("c".to_owned()).spanning(Span::from(((18, 0), (19, 0)))),
+        Token::Definition.spanning(Span::from(((20, 0), (21, 0)))),
+        Token::EndOption.spanning(Span::from(((22, 0), (23, 0)))),
+        Token::Terminator.spanning(Span::from(((24, 0), (25, 0)))),
+        Token::Nonterminal("d".to_owned()).spanning(Span::from(((26, 0), (27, 0)))),
+        Token::Definition.spanning(Span::from(((28, 0), (29, 0)))),
+        Token::Terminal("z".to_owned()).spanning(Span::from(((30, 0), (33, 0)))),
+        Token::Terminator.spanning(Span::from(((33, 0), (34, 0)))),
+    ]);
+    assert_eq!(
+        errors,
+        vec![
+            Error::Unexpected {
+                expected: vec![
+                    Expected::Exception,
+                    Expected::Concatenation,
+                    Expected::DefinitionSeparator,
+                    Expected::Terminator,
+                ],
+                found: Some(Token::Terminal("y".to_owned())),
+            }
+            .spanning(Span::from(((14, 0), (17, 0)))),
+            Error::Unexpected {
+                expected: vec![
+                    Expected::Integer,
+                    Expected::StartOption,
+                    Expected::StartRepeat,
+                    Expected::StartGroup,
+                    Expected::Nonterminal,
+                    Expected::Terminal,
+                    Expected::Special,
+                    Expected::Exception,
+                    Expected::Concatenation,
+                    Expected::DefinitionSeparator,
+                    Expected::Terminator,
+                ],
+                found: Some(Token::EndOption),
+            }
+            .spanning(Span::from(((22, 0), (23, 0)))),
+        ]
+    );
+    assert_eq!(
+        errors[0].to_string(),
+        "expected `-`, `,`, `|` or `;` but found terminal 'y'"
+    );
+    assert_eq!(
+        grammar.node.productions[1..3],
+        [
+            Production {
+                lhs: "b".to_owned().spanning(Span::from(((6, 0), (7, 0)))),
+                rhs: Expression::Error.spanning(Span::from(((10, 0), (17, 0)))),
+            }
+            .spanning(Span::from(((6, 0), (17, 0)))),
+            Production {
+                lhs: "c".to_owned().spanning(Span::from(((18, 0), (19, 0)))),
+                rhs: Expression::Error.spanning(Span::from(((22, 0), (25, 0)))),
+            }
+            .spanning(Span::from(((18, 0), (25, 0)))),
+        ]
     );
+    let names = grammar
+        .node
+        .productions
+        .iter()
+        .map(|production| production.node.lhs.node.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["a", "b", "c", "d"]);
 }
 
 use quickcheck::{Arbitrary, Gen};
@@ -555,6 +647,8 @@ impl Arbitrary for Location {
         Location {
             column: usize::arbitrary(g),
             line: usize::arbitrary(g),
+            offset: usize::arbitrary(g),
+            utf16_column: usize::arbitrary(g),
         }
     }
 }
@@ -1,18 +1,32 @@
-use super::{Span, Spanned, Token};
+use super::error::{Error, Expected};
+use super::{Span, Spanned, Spanning, Token};
 use nom::{
     Compare, CompareResult, FindSubstring, FindToken, InputIter, InputLength, InputTake, Needed,
     Slice, UnspecializedInput,
 };
 use std::{
+    cell::RefCell,
     iter::{Cloned, Enumerate},
     ops::{Range, RangeFrom, RangeFull, RangeTo},
+    rc::Rc,
     slice::Iter,
 };
 
+/// What every parser that failed at the furthest token would have accepted.
+#[derive(Debug, Default)]
+struct Furthest {
+    /// Number of tokens left at the furthest failure.
+    remaining: Option<usize>,
+    span: Span,
+    found: Option<Token>,
+    expected: Vec<Expected>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Tokens<'a> {
     inner: &'a [Spanned<Token>],
     last_span: Span,
+    furthest: Rc<RefCell<Furthest>>,
 }
 
 impl<'a> Tokens<'a> {
@@ -24,12 +38,51 @@ impl<'a> Tokens<'a> {
         Tokens {
             inner: tokens,
             last_span: first_span,
+            furthest: Rc::new(RefCell::new(Furthest::default())),
         }
     }
 
     pub fn last_span(&self) -> Span {
         self.last_span
     }
+
+    /// Records that `expected` would have been accepted at the first token.
+    pub fn expect(&self, expected: Expected) {
+        let mut furthest = self.furthest.borrow_mut();
+        match furthest.remaining {
+            Some(remaining) if remaining < self.inner.len() => return,
+            Some(remaining) if remaining == self.inner.len() => {}
+            _ => {
+                let (span, found) = match self.inner.first() {
+                    Some(token) => (token.span, Some(token.node.clone())),
+                    None => (self.last_span, None),
+                };
+                *furthest = Furthest {
+                    remaining: Some(self.inner.len()),
+                    span,
+                    found,
+                    expected: Vec::new(),
+                };
+            }
+        }
+        if !furthest.expected.contains(&expected) {
+            furthest.expected.push(expected);
+        }
+    }
+
+    /// An error listing everything that would have been accepted at the
+    /// furthest token reached, shared by all tokens sliced from this one.
+    pub fn furthest_error(&self) -> Option<Spanned<Error>> {
+        let furthest = self.furthest.borrow();
+        furthest.remaining?;
+        Some(
+            Error::Unexpected {
+                expected: furthest.expected.clone(),
+                found: furthest.found.clone(),
+            }
+            .spanning(furthest.span),
+        )
+    }
 }
 
 impl<'a> PartialEq for Tokens<'a> {
@@ -87,6 +140,7 @@ where
         Tokens {
             inner: &self.inner[0..count],
             last_span: self.last_span,
+            furthest: self.furthest.clone(),
         }
     }
 
@@ -101,10 +155,12 @@ where
             Tokens {
                 inner: suffix,
                 last_span: self.last_span,
+                furthest: self.furthest.clone(),
             },
             Tokens {
                 inner: prefix,
                 last_span,
+                furthest: self.furthest.clone(),
             },
         )
     }
@@ -149,6 +205,7 @@ impl<'a> Slice<Range<usize>> for Tokens<'a> {
         Tokens {
             inner: &self.inner[range],
             last_span,
+            furthest: self.furthest.clone(),
         }
     }
 }
@@ -158,6 +215,7 @@ impl<'a> Slice<RangeTo<usize>> for Tokens<'a> {
         Tokens {
             inner: &self.inner[range],
             last_span: self.last_span,
+            furthest: self.furthest.clone(),
         }
     }
 }
@@ -172,6 +230,7 @@ impl<'a> Slice<RangeFrom<usize>> for Tokens<'a> {
         Tokens {
             inner: &self.inner[range],
             last_span,
+            furthest: self.furthest.clone(),
         }
     }
 }
@@ -181,6 +240,7 @@ impl<'a> Slice<RangeFull> for Tokens<'a> {
         Tokens {
             inner: &self.inner[range],
             last_span: self.last_span,
+            furthest: self.furthest.clone(),
         }
     }
 }
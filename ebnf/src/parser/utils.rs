@@ -1,17 +1,23 @@
-use super::{Error, Expression, Span, Spanned, Spanning, Token, Tokens};
+use super::{Error, Expected, Expression, Span, Spanned, Spanning, Token, Tokens};
 use nom::{error::ParseError, Err, IResult, InputIter, InputLength, Parser, Slice};
 
 #[macro_export]
 macro_rules! literal {
-    ($name:ident, $kind:pat, $error:expr) => {
+    ($name:ident, $kind:pat, $error:expr, $expected:expr) => {
         pub fn $name(i: Tokens) -> IResult<Tokens, Spanned<Token>, Spanned<Error>> {
             match i.iter_elements().next() {
                 Some(Spanned {
                     node: kind @ $kind,
                     span,
                 }) => Ok((i.slice(1..), kind.spanning(span))),
-                Some(Spanned { span, .. }) => Err(Err::Error($error.spanning(span))),
-                None => Err(Err::Error($error.spanning(i.last_span()))),
+                Some(Spanned { span, .. }) => {
+                    i.expect($expected);
+                    Err(Err::Error($error.spanning(span)))
+                }
+                None => {
+                    i.expect($expected);
+                    Err(Err::Error($error.spanning(i.last_span())))
+                }
             }
         }
     };
@@ -20,62 +26,74 @@ macro_rules! literal {
 literal!(
     concatenation_symbol,
     Token::Concatenation,
-    Error::ConcatenationSymbolExpected
+    Error::ConcatenationSymbolExpected,
+    Expected::Concatenation
 );
 literal!(
     definition_symbol,
     Token::Definition,
-    Error::DefinitionSymbolExpected
+    Error::DefinitionSymbolExpected,
+    Expected::Definition
 );
 literal!(
     definition_separator,
     Token::DefinitionSeparator,
-    Error::DefinitionSeparatorSymbolExpected
+    Error::DefinitionSeparatorSymbolExpected,
+    Expected::DefinitionSeparator
 );
 literal!(
     end_group_symbol,
     Token::EndGroup,
-    Error::EndGroupSymbolExpected
+    Error::EndGroupSymbolExpected,
+    Expected::EndGroup
 );
 literal!(
     end_option_symbol,
     Token::EndOption,
-    Error::EndOptionSymbolExpected
+    Error::EndOptionSymbolExpected,
+    Expected::EndOption
 );
 literal!(
     end_repeat_symbol,
     Token::EndRepeat,
-    Error::EndRepeatSymbolExpected
+    Error::EndRepeatSymbolExpected,
+    Expected::EndRepeat
 );
 literal!(
     exception_symbol,
     Token::Exception,
-    Error::ExceptionSymbolExpected
+    Error::ExceptionSymbolExpected,
+    Expected::Exception
 );
 literal!(
     repetition_symbol,
     Token::Repetition,
-    Error::RepetitionSymbolExpected
+    Error::RepetitionSymbolExpected,
+    Expected::Repetition
 );
 literal!(
     start_group_symbol,
     Token::StartGroup,
-    Error::StartGroupSymbolExpected
+    Error::StartGroupSymbolExpected,
+    Expected::StartGroup
 );
 literal!(
     start_option_symbol,
     Token::StartOption,
-    Error::StartOptionSymbolExpected
+    Error::StartOptionSymbolExpected,
+    Expected::StartOption
 );
 literal!(
     start_repeat_symbol,
     Token::StartRepeat,
-    Error::StartRepeatSymbolExpected
+    Error::StartRepeatSymbolExpected,
+    Expected::StartRepeat
 );
 literal!(
     terminator_symbol,
     Token::Terminator,
-    Error::TerminatorSymbolExpected
+    Error::TerminatorSymbolExpected,
+    Expected::Terminator
 );
 
 pub fn identifier(i: Tokens) -> IResult<Tokens, Spanned<String>, Spanned<Error>> {
@@ -84,10 +102,14 @@ pub fn identifier(i: Tokens) -> IResult<Tokens, Spanned<String>, Spanned<Error>>
             node: Token::Nonterminal(s),
             span,
         }) => Ok((i.slice(1..), s.spanning(span))),
-        Some(Spanned { span, .. }) => Err(Err::Error(Error::IdentifierExpected.spanning(span))),
-        None => Err(Err::Error(
-            Error::IdentifierExpected.spanning(i.last_span()),
-        )),
+        Some(Spanned { span, .. }) => {
+            i.expect(Expected::Identifier);
+            Err(Err::Error(Error::IdentifierExpected.spanning(span)))
+        }
+        None => {
+            i.expect(Expected::Identifier);
+            Err(Err::Error(Error::IdentifierExpected.spanning(i.last_span())))
+        }
     }
 }
 
@@ -97,10 +119,14 @@ pub fn nonterminal(i: Tokens) -> IResult<Tokens, Spanned<Expression>, Spanned<Er
             node: Token::Nonterminal(s),
             span,
         }) => Ok((i.slice(1..), Expression::Nonterminal(s).spanning(span))),
-        Some(Spanned { span, .. }) => Err(Err::Error(Error::NonterminalExpected.spanning(span))),
-        None => Err(Err::Error(
-            Error::NonterminalExpected.spanning(i.last_span()),
-        )),
+        Some(Spanned { span, .. }) => {
+            i.expect(Expected::Nonterminal);
+            Err(Err::Error(Error::NonterminalExpected.spanning(span)))
+        }
+        None => {
+            i.expect(Expected::Nonterminal);
+            Err(Err::Error(Error::NonterminalExpected.spanning(i.last_span())))
+        }
     }
 }
 
@@ -110,8 +136,14 @@ pub fn terminal(i: Tokens) -> IResult<Tokens, Spanned<Expression>, Spanned<Error
             node: Token::Terminal(s),
             span,
         }) => Ok((i.slice(1..), Expression::Terminal(s).spanning(span))),
-        Some(Spanned { span, .. }) => Err(Err::Error(Error::TerminalExpected.spanning(span))),
-        None => Err(Err::Error(Error::TerminalExpected.spanning(i.last_span()))),
+        Some(Spanned { span, .. }) => {
+            i.expect(Expected::Terminal);
+            Err(Err::Error(Error::TerminalExpected.spanning(span)))
+        }
+        None => {
+            i.expect(Expected::Terminal);
+            Err(Err::Error(Error::TerminalExpected.spanning(i.last_span())))
+        }
     }
 }
 
@@ -121,8 +153,14 @@ pub fn special(i: Tokens) -> IResult<Tokens, Spanned<Expression>, Spanned<Error>
             node: Token::Special(s),
             span,
         }) => Ok((i.slice(1..), Expression::Special(s).spanning(span))),
-        Some(Spanned { span, .. }) => Err(Err::Error(Error::SpecialExpected.spanning(span))),
-        None => Err(Err::Error(Error::SpecialExpected.spanning(i.last_span()))),
+        Some(Spanned { span, .. }) => {
+            i.expect(Expected::Special);
+            Err(Err::Error(Error::SpecialExpected.spanning(span)))
+        }
+        None => {
+            i.expect(Expected::Special);
+            Err(Err::Error(Error::SpecialExpected.spanning(i.last_span())))
+        }
     }
 }
 
@@ -132,8 +170,14 @@ pub fn integer(i: Tokens) -> IResult<Tokens, Spanned<usize>, Spanned<Error>> {
             node: Token::Integer(s),
             span,
         }) => Ok((i.slice(1..), s.spanning(span))),
-        Some(Spanned { span, .. }) => Err(Err::Error(Error::IntegerExpected.spanning(span))),
-        None => Err(Err::Error(Error::IntegerExpected.spanning(i.last_span()))),
+        Some(Spanned { span, .. }) => {
+            i.expect(Expected::Integer);
+            Err(Err::Error(Error::IntegerExpected.spanning(span)))
+        }
+        None => {
+            i.expect(Expected::Integer);
+            Err(Err::Error(Error::IntegerExpected.spanning(i.last_span())))
+        }
     }
 }
 
@@ -153,6 +197,7 @@ where
 {
     move |input: Tokens| {
         if input.input_len() == 0 {
+            input.expect(Expected::Identifier);
             Err(Err::Error(
                 Error::IdentifierExpected.spanning(input.last_span()),
             ))
@@ -0,0 +1,355 @@
+//! Traversals of spanned [`Expression`] trees, like those of `base::visit`.
+//! Every method is also given the span of its expression.
+
+use super::ast::Expression;
+use super::{Span, Spanned, Spanning};
+
+#[cfg(test)]
+mod tests;
+
+/// Walks an expression by reference.
+pub trait Visitor<'a> {
+    fn visit_expression(&mut self, expression: &'a Spanned<Expression>) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_alternative(
+        &mut self,
+        first: &'a Spanned<Expression>,
+        second: &'a Spanned<Expression>,
+        rest: &'a [Spanned<Expression>],
+        _span: Span,
+    ) {
+        self.visit_expression(first);
+        self.visit_expression(second);
+        for expression in rest.iter() {
+            self.visit_expression(expression);
+        }
+    }
+
+    fn visit_sequence(
+        &mut self,
+        first: &'a Spanned<Expression>,
+        second: &'a Spanned<Expression>,
+        rest: &'a [Spanned<Expression>],
+        _span: Span,
+    ) {
+        self.visit_expression(first);
+        self.visit_expression(second);
+        for expression in rest.iter() {
+            self.visit_expression(expression);
+        }
+    }
+
+    fn visit_optional(&mut self, inner: &'a Spanned<Expression>, _span: Span) {
+        self.visit_expression(inner);
+    }
+
+    fn visit_repeated(&mut self, inner: &'a Spanned<Expression>, _span: Span) {
+        self.visit_expression(inner);
+    }
+
+    fn visit_factor(
+        &mut self,
+        _count: &'a Spanned<usize>,
+        primary: &'a Spanned<Expression>,
+        _span: Span,
+    ) {
+        self.visit_expression(primary);
+    }
+
+    fn visit_exception(
+        &mut self,
+        subject: &'a Spanned<Expression>,
+        restriction: &'a Spanned<Expression>,
+        _span: Span,
+    ) {
+        self.visit_expression(subject);
+        self.visit_expression(restriction);
+    }
+
+    fn visit_nonterminal(&mut self, _identifier: &'a str, _span: Span) {}
+
+    fn visit_terminal(&mut self, _content: &'a str, _span: Span) {}
+
+    fn visit_special(&mut self, _content: &'a str, _span: Span) {}
+
+    fn visit_empty(&mut self, _span: Span) {}
+
+    fn visit_error(&mut self, _span: Span) {}
+}
+
+/// Calls the method of `visitor` for the kind of `expression`.
+pub fn walk_expression<'a, V>(visitor: &mut V, expression: &'a Spanned<Expression>)
+where
+    V: Visitor<'a> + ?Sized,
+{
+    let span = expression.span;
+    match &expression.node {
+        Expression::Alternative {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_alternative(first, second, rest, span),
+        Expression::Sequence {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_sequence(first, second, rest, span),
+        Expression::Optional(box inner) => visitor.visit_optional(inner, span),
+        Expression::Repeated(box inner) => visitor.visit_repeated(inner, span),
+        Expression::Factor {
+            count,
+            primary: box primary,
+        } => visitor.visit_factor(count, primary, span),
+        Expression::Exception {
+            subject: box subject,
+            restriction: box restriction,
+        } => visitor.visit_exception(subject, restriction, span),
+        Expression::Nonterminal(identifier) => visitor.visit_nonterminal(identifier, span),
+        Expression::Terminal(content) => visitor.visit_terminal(content, span),
+        Expression::Special(content) => visitor.visit_special(content, span),
+        Expression::Empty => visitor.visit_empty(span),
+        Expression::Error => visitor.visit_error(span),
+    }
+}
+
+/// Walks an expression by mutable reference, to change it in place.
+pub trait VisitorMut {
+    fn visit_expression_mut(&mut self, expression: &mut Spanned<Expression>) {
+        walk_expression_mut(self, expression);
+    }
+
+    fn visit_alternative_mut(
+        &mut self,
+        first: &mut Spanned<Expression>,
+        second: &mut Spanned<Expression>,
+        rest: &mut Vec<Spanned<Expression>>,
+        _span: Span,
+    ) {
+        self.visit_expression_mut(first);
+        self.visit_expression_mut(second);
+        for expression in rest.iter_mut() {
+            self.visit_expression_mut(expression);
+        }
+    }
+
+    fn visit_sequence_mut(
+        &mut self,
+        first: &mut Spanned<Expression>,
+        second: &mut Spanned<Expression>,
+        rest: &mut Vec<Spanned<Expression>>,
+        _span: Span,
+    ) {
+        self.visit_expression_mut(first);
+        self.visit_expression_mut(second);
+        for expression in rest.iter_mut() {
+            self.visit_expression_mut(expression);
+        }
+    }
+
+    fn visit_optional_mut(&mut self, inner: &mut Spanned<Expression>, _span: Span) {
+        self.visit_expression_mut(inner);
+    }
+
+    fn visit_repeated_mut(&mut self, inner: &mut Spanned<Expression>, _span: Span) {
+        self.visit_expression_mut(inner);
+    }
+
+    fn visit_factor_mut(
+        &mut self,
+        _count: &mut Spanned<usize>,
+        primary: &mut Spanned<Expression>,
+        _span: Span,
+    ) {
+        self.visit_expression_mut(primary);
+    }
+
+    fn visit_exception_mut(
+        &mut self,
+        subject: &mut Spanned<Expression>,
+        restriction: &mut Spanned<Expression>,
+        _span: Span,
+    ) {
+        self.visit_expression_mut(subject);
+        self.visit_expression_mut(restriction);
+    }
+
+    fn visit_nonterminal_mut(&mut self, _identifier: &mut String, _span: Span) {}
+
+    fn visit_terminal_mut(&mut self, _content: &mut String, _span: Span) {}
+
+    fn visit_special_mut(&mut self, _content: &mut String, _span: Span) {}
+
+    fn visit_empty_mut(&mut self, _span: Span) {}
+
+    fn visit_error_mut(&mut self, _span: Span) {}
+}
+
+/// Calls the method of `visitor` for the kind of `expression`.
+pub fn walk_expression_mut<V>(visitor: &mut V, expression: &mut Spanned<Expression>)
+where
+    V: VisitorMut + ?Sized,
+{
+    let span = expression.span;
+    match &mut expression.node {
+        Expression::Alternative {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_alternative_mut(first, second, rest, span),
+        Expression::Sequence {
+            first: box first,
+            second: box second,
+            rest,
+        } => visitor.visit_sequence_mut(first, second, rest, span),
+        Expression::Optional(box inner) => visitor.visit_optional_mut(inner, span),
+        Expression::Repeated(box inner) => visitor.visit_repeated_mut(inner, span),
+        Expression::Factor {
+            count,
+            primary: box primary,
+        } => visitor.visit_factor_mut(count, primary, span),
+        Expression::Exception {
+            subject: box subject,
+            restriction: box restriction,
+        } => visitor.visit_exception_mut(subject, restriction, span),
+        Expression::Nonterminal(identifier) => visitor.visit_nonterminal_mut(identifier, span),
+        Expression::Terminal(content) => visitor.visit_terminal_mut(content, span),
+        Expression::Special(content) => visitor.visit_special_mut(content, span),
+        Expression::Empty => visitor.visit_empty_mut(span),
+        Expression::Error => visitor.visit_error_mut(span),
+    }
+}
+
+/// Rebuilds an expression out of its parts, each of which may be replaced
+/// with an expression of another kind.
+pub trait Fold {
+    fn fold_expression(&mut self, expression: Spanned<Expression>) -> Spanned<Expression> {
+        walk_fold(self, expression)
+    }
+
+    fn fold_alternative(
+        &mut self,
+        first: Spanned<Expression>,
+        second: Spanned<Expression>,
+        rest: Vec<Spanned<Expression>>,
+        span: Span,
+    ) -> Spanned<Expression> {
+        Expression::Alternative {
+            first: Box::new(self.fold_expression(first)),
+            second: Box::new(self.fold_expression(second)),
+            rest: rest
+                .into_iter()
+                .map(|expression| self.fold_expression(expression))
+                .collect(),
+        }
+        .spanning(span)
+    }
+
+    fn fold_sequence(
+        &mut self,
+        first: Spanned<Expression>,
+        second: Spanned<Expression>,
+        rest: Vec<Spanned<Expression>>,
+        span: Span,
+    ) -> Spanned<Expression> {
+        Expression::Sequence {
+            first: Box::new(self.fold_expression(first)),
+            second: Box::new(self.fold_expression(second)),
+            rest: rest
+                .into_iter()
+                .map(|expression| self.fold_expression(expression))
+                .collect(),
+        }
+        .spanning(span)
+    }
+
+    fn fold_optional(&mut self, inner: Spanned<Expression>, span: Span) -> Spanned<Expression> {
+        Expression::Optional(Box::new(self.fold_expression(inner))).spanning(span)
+    }
+
+    fn fold_repeated(&mut self, inner: Spanned<Expression>, span: Span) -> Spanned<Expression> {
+        Expression::Repeated(Box::new(self.fold_expression(inner))).spanning(span)
+    }
+
+    fn fold_factor(
+        &mut self,
+        count: Spanned<usize>,
+        primary: Spanned<Expression>,
+        span: Span,
+    ) -> Spanned<Expression> {
+        Expression::Factor {
+            count,
+            primary: Box::new(self.fold_expression(primary)),
+        }
+        .spanning(span)
+    }
+
+    fn fold_exception(
+        &mut self,
+        subject: Spanned<Expression>,
+        restriction: Spanned<Expression>,
+        span: Span,
+    ) -> Spanned<Expression> {
+        Expression::Exception {
+            subject: Box::new(self.fold_expression(subject)),
+            restriction: Box::new(self.fold_expression(restriction)),
+        }
+        .spanning(span)
+    }
+
+    fn fold_nonterminal(&mut self, identifier: String, span: Span) -> Spanned<Expression> {
+        Expression::Nonterminal(identifier).spanning(span)
+    }
+
+    fn fold_terminal(&mut self, content: String, span: Span) -> Spanned<Expression> {
+        Expression::Terminal(content).spanning(span)
+    }
+
+    fn fold_special(&mut self, content: String, span: Span) -> Spanned<Expression> {
+        Expression::Special(content).spanning(span)
+    }
+
+    fn fold_empty(&mut self, span: Span) -> Spanned<Expression> {
+        Expression::Empty.spanning(span)
+    }
+
+    fn fold_error(&mut self, span: Span) -> Spanned<Expression> {
+        Expression::Error.spanning(span)
+    }
+}
+
+/// Calls the method of `folder` for the kind of `expression`.
+pub fn walk_fold<F>(folder: &mut F, expression: Spanned<Expression>) -> Spanned<Expression>
+where
+    F: Fold + ?Sized,
+{
+    let span = expression.span;
+    match expression.node {
+        Expression::Alternative {
+            first: box first,
+            second: box second,
+            rest,
+        } => folder.fold_alternative(first, second, rest, span),
+        Expression::Sequence {
+            first: box first,
+            second: box second,
+            rest,
+        } => folder.fold_sequence(first, second, rest, span),
+        Expression::Optional(box inner) => folder.fold_optional(inner, span),
+        Expression::Repeated(box inner) => folder.fold_repeated(inner, span),
+        Expression::Factor {
+            count,
+            primary: box primary,
+        } => folder.fold_factor(count, primary, span),
+        Expression::Exception {
+            subject: box subject,
+            restriction: box restriction,
+        } => folder.fold_exception(subject, restriction, span),
+        Expression::Nonterminal(identifier) => folder.fold_nonterminal(identifier, span),
+        Expression::Terminal(content) => folder.fold_terminal(content, span),
+        Expression::Special(content) => folder.fold_special(content, span),
+        Expression::Empty => folder.fold_empty(span),
+        Expression::Error => folder.fold_error(span),
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Fold, Visitor, VisitorMut};
+use crate::lexer;
+use crate::parser::{self, Expression, Span, Spanned};
+
+fn parse(input: &str) -> Spanned<Expression> {
+    let (tokens, _) = lexer::lex(input);
+    let (grammar, _) = parser::parse(&tokens);
+    grammar.node.productions[0].node.rhs.clone()
+}
+
+#[test]
+fn test_visitor() {
+    #[derive(Default)]
+    struct Collect<'a> {
+        nonterminals: Vec<(&'a str, usize)>,
+        factors: Vec<usize>,
+    }
+
+    impl<'a> Visitor<'a> for Collect<'a> {
+        fn visit_nonterminal(&mut self, identifier: &'a str, span: Span) {
+            self.nonterminals.push((identifier, span.from.column));
+        }
+
+        fn visit_factor(
+            &mut self,
+            count: &'a Spanned<usize>,
+            primary: &'a Spanned<Expression>,
+            _: Span,
+        ) {
+            self.factors.push(count.node);
+            self.visit_expression(primary);
+        }
+    }
+
+    let expression = parse("a = b, [ 2 * c - 'x' ] | { 3 * ?d? };");
+    let mut collect = Collect::default();
+    collect.visit_expression(&expression);
+    assert_eq!(collect.nonterminals, vec![("b", 4), ("c", 13)]);
+    assert_eq!(collect.factors, vec![2, 3]);
+}
+
+#[test]
+fn test_visitor_mut() {
+    struct Rename;
+
+    impl VisitorMut for Rename {
+        fn visit_nonterminal_mut(&mut self, identifier: &mut String, _: Span) {
+            identifier.push('2');
+        }
+    }
+
+    let mut expression = parse("a = b, [ c - 'x' ];");
+    Rename.visit_expression_mut(&mut expression);
+    assert_eq!(
+        strip(expression.node),
+        strip(parse("a = b2, [ c2 - 'x' ];").node)
+    );
+}
+
+/// Replaces every span with the default one.
+fn strip(expression: Expression) -> Expression {
+    struct Strip;
+
+    impl Fold for Strip {
+        fn fold_expression(&mut self, expression: Spanned<Expression>) -> Spanned<Expression> {
+            let mut folded = super::walk_fold(self, expression);
+            folded.span = Span::new();
+            if let Expression::Factor { count, .. } = &mut folded.node {
+                count.span = Span::new();
+            }
+            folded
+        }
+    }
+
+    Strip
+        .fold_expression(Spanned {
+            node: expression,
+            span: Span::new(),
+        })
+        .node
+}
+
+#[test]
+fn test_fold() {
+    // rewrites [ x ] into ( x | )
+    struct Desugar;
+
+    impl Fold for Desugar {
+        fn fold_optional(&mut self, inner: Spanned<Expression>, span: Span) -> Spanned<Expression> {
+            let inner = self.fold_expression(inner);
+            let empty = Spanned {
+                node: Expression::Empty,
+                span: Span::between(&inner.span, &span),
+            };
+            Spanned {
+                node: Expression::Alternative {
+                    first: Box::new(inner),
+                    second: Box::new(empty),
+                    rest: vec![],
+                },
+                span,
+            }
+        }
+    }
+
+    let expression = Desugar.fold_expression(parse("a = b, [ [ c ] ];"));
+    assert_eq!(
+        strip(expression.node),
+        strip(parse("a = b, (( c | ) | );").node)
+    );
+}
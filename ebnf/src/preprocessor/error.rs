@@ -4,15 +4,25 @@ use std::fmt;
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
-    UndefinedRule(String),
+    /// A rule used but never defined, along with a defined rule of a
+    /// similar name, if there is one.
+    UndefinedRule(String, Option<String>),
     LeftRecursion(Vec<String>),
-    MultipleDefinitions(String),
+    /// A rule defined again, along with where it was first defined.
+    MultipleDefinitions(String, Span),
+    UnknownSpecial(String),
+    /// A start rule that isn't defined, along with a defined rule of a
+    /// similar name, if there is one.
+    UndefinedStart(String, Option<String>),
+    /// A production the parser couldn't make sense of, which is left in the
+    /// grammar in place of its expression.
+    SyntaxError,
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Error::UndefinedRule(rule) => write!(f, "rule {} is undefined", rule),
+            Error::UndefinedRule(rule, _) => write!(f, "rule {} is undefined", rule),
             Error::LeftRecursion(chain) => {
                 let chain_string = chain
                     .iter()
@@ -22,7 +32,12 @@ impl fmt::Display for Error {
                 let rule = chain.first().unwrap();
                 return write!(f, "rule {} is left recursive ({})", rule, chain_string);
             },
-            Error::MultipleDefinitions(rule) => write!(f, "rule {} is multiply defined", rule),
+            Error::MultipleDefinitions(rule, _) => write!(f, "rule {} is multiply defined", rule),
+            Error::UnknownSpecial(content) => {
+                write!(f, "special sequence ?{}? is unknown", content)
+            }
+            Error::UndefinedStart(rule, _) => write!(f, "start rule {} is undefined", rule),
+            Error::SyntaxError => write!(f, "production has a syntax error"),
         }
     }
 }
@@ -1,261 +1,136 @@
 pub mod error;
+pub mod warning;
 #[cfg(test)]
 mod tests;
 
+use super::compiler::lower;
+use super::parser::visit::Visitor;
 use super::parser::{Expression, Grammar, Production};
 use super::span::{Span, Spanned, Spanning};
+use super::Options;
+use base::SpecialRegistry;
 use error::Error;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use warning::Warning;
 
-fn is_failing(
-    expression: &Spanned<Expression>,
-    rules: &HashMap<String, &Spanned<Expression>>,
-    trace: &mut Vec<String>,
-) -> bool {
-    match &expression.node {
-        Expression::Alternative {
-            first: box first,
-            second: box second,
-            rest,
-        } => {
-            if !is_failing(first, rules, trace) {
-                return false;
-            }
-            if !is_failing(second, rules, trace) {
-                return false;
-            }
-            for expression in rest.iter() {
-                if !is_failing(expression, rules, trace) {
-                    return false;
-                }
-            }
-            true
-        }
-        Expression::Sequence {
-            first: box first,
-            second: box second,
-            rest,
-        } => {
-            if is_failing(first, rules, trace) {
-                return true;
-            }
-            if is_failing(second, rules, trace) {
-                return true;
-            }
-            for expression in rest.iter() {
-                if is_failing(expression, rules, trace) {
-                    return true;
-                }
-            }
-            false
-        }
-        Expression::Optional(_) => true,
-        Expression::Repeated(_) => true,
-        Expression::Factor {
-            count: Spanned { node: count, .. },
-            primary: box primary,
-        } => {
-            if *count == 0 {
-                return true;
-            }
-            is_failing(primary, rules, trace)
-        }
-        Expression::Exception {
-            subject: box subject,
-            restriction: box restriction,
-        } => {
-            is_failing(subject, rules, trace) && is_failing(restriction, rules, trace)
-        }
-        Expression::Nonterminal(identifier) => {
-            if !trace.contains(identifier) {
-                if let Some(expression) = rules.get(identifier) {
-                    trace.push(identifier.clone());
-                    let result = is_failing(&expression, rules, trace);
-                    trace.pop().unwrap();
-
-                    return result;
-                }
-            }
+/// Where an expression first uses a rule.
+struct FirstUse<'r> {
+    rule: &'r str,
+    span: Option<Span>,
+}
 
-            false
+impl<'a, 'r> Visitor<'a> for FirstUse<'r> {
+    fn visit_nonterminal(&mut self, identifier: &'a str, span: Span) {
+        if self.span.is_none() && identifier == self.rule {
+            self.span = Some(span);
         }
-        Expression::Terminal(_) => false,
-        Expression::Special(_) => false,
-        Expression::Empty => true,
     }
 }
 
-fn check_expr(
-    expression: &Spanned<Expression>,
-    rules: &HashMap<String, &Spanned<Expression>>,
-    trace: &mut Vec<String>,
-) -> Result<(), Spanned<Error>> {
-    match &expression.node {
-        Expression::Alternative {
-            first: box first,
-            second: box second,
-            rest,
-        } => {
-            check_expr(first, rules, trace)?;
-            check_expr(second, rules, trace)?;
-            for expression in rest.iter() {
-                check_expr(expression, rules, trace)?;
-            }
-            Ok(())
-        }
-        Expression::Sequence {
-            first: box first,
-            second: box second,
-            rest,
-        } => {
-            if !is_failing(first, rules, &mut vec![trace.last().unwrap().clone()]) {
-                return check_expr(first, rules, trace);
-            }
-            if rest.is_empty() {
-                check_expr(second, rules, trace)
-            } else {
-                if !is_failing(second, rules, &mut vec![trace.last().unwrap().clone()]) {
-                    return check_expr(second, rules, trace);
-                }
-                for expression in rest[..rest.len() - 1].iter() {
-                    if !is_failing(expression, rules, &mut vec![trace.last().unwrap().clone()]) {
-                        return check_expr(expression, rules, trace);
-                    }
-                }
-                return check_expr(&rest.last().unwrap(), rules, trace);
-            }
-        }
-        Expression::Optional(box inner) => {
-            check_expr(inner, rules, trace)
-        }
-        Expression::Repeated(box inner) => {
-            check_expr(inner, rules, trace)
-        }
-        Expression::Factor {
-            count: Spanned { node: count, .. },
-            primary: box primary,
-        } => {
-            if *count == 0 {
-                return Ok(());
-            }
-            check_expr(primary, rules, trace)
-        }
-        Expression::Exception {
-            subject: box subject,
-            restriction: box restriction,
-        } => {
-            check_expr(subject, rules, trace)?;
-            check_expr(restriction, rules, trace)?;
-            Ok(())
-        }
-        Expression::Nonterminal(identifier) => {
-            if &trace[0] == identifier {
-                trace.push(identifier.clone());
-                return Err(Error::LeftRecursion(trace.clone()).spanning(expression.span));
-            }
-
-            if !trace.contains(identifier) {
-                if let Some(node) = rules.get(identifier) {
-                    trace.push(identifier.clone());
-                    let result = check_expr(&node, rules, trace);
-                    trace.pop().unwrap();
-
-                    return result;
-                }
-            }
+/// Reports each rule that may check itself again before consuming input,
+/// spanning the first use of it in the last rule of its chain. Productions
+/// with syntax errors are left out, as they are reported on their own.
+pub fn validate_left_recursion(
+    Spanned { node: grammar, .. }: &Spanned<Grammar>,
+    specials: &SpecialRegistry,
+    errors: &mut Vec<Spanned<Error>>,
+) {
+    let productions = grammar
+        .productions
+        .iter()
+        .map(|Spanned { node: production, .. }| production)
+        .filter(|production| !has_syntax_error(&production.rhs))
+        .collect::<Vec<_>>();
+    let compiled = productions
+        .iter()
+        .map(|production| (production.lhs.node.clone(), lower(&production.rhs)))
+        .collect::<base::Grammar>();
+    // like the compiled grammar, rules are checked with their last definition
+    let definitions = productions
+        .iter()
+        .enumerate()
+        .map(|(index, production)| (production.lhs.node.as_str(), index))
+        .collect::<HashMap<_, _>>();
+    let mut chains = base::left_recursion(&compiled, specials)
+        .into_iter()
+        .map(|chain| (chain[0].clone(), chain))
+        .collect::<HashMap<_, _>>();
 
-            Ok(())
+    for (index, production) in productions.iter().enumerate() {
+        if definitions[production.lhs.node.as_str()] != index {
+            continue;
+        }
+        if let Some(chain) = chains.remove(&production.lhs.node) {
+            let last = &chain[chain.len() - 2];
+            let mut first_use = FirstUse {
+                rule: &chain[0],
+                span: None,
+            };
+            first_use.visit_expression(&productions[definitions[last.as_str()]].rhs);
+            let span = first_use.span.unwrap_or(production.lhs.span);
+            errors.push(Error::LeftRecursion(chain).spanning(span));
         }
-        Expression::Terminal(_) => Ok(()),
-        Expression::Special(_) => Ok(()),
-        Expression::Empty => Ok(()),
     }
 }
 
-fn get_rule_hash_map(rules: &[Spanned<Production>]) -> HashMap<String, &Spanned<Expression>> {
-    rules
-        .iter()
-        .map(
-            |Spanned { node: rule, .. }| -> (String, &Spanned<Expression>) {
-                (rule.lhs.node.clone(), &rule.rhs)
-            },
-        )
-        .collect()
+/// Reports the rules an expression uses that are not in `rules`.
+struct UndefinedRules<'r> {
+    rules: &'r [String],
+    errors: &'r mut Vec<Spanned<Error>>,
 }
 
-fn validate_left_recursion(
-    Spanned { node: grammar, .. }: &Spanned<Grammar>,
-) -> Result<(), Spanned<Error>> {
-    let rules = get_rule_hash_map(&grammar.productions);
-
-    for (name, expression) in &rules {
-        let name = name.clone();
-        check_expr(expression, &rules, &mut vec![name])?;
+impl<'a, 'r> Visitor<'a> for UndefinedRules<'r> {
+    fn visit_nonterminal(&mut self, identifier: &'a str, span: Span) {
+        if !self.rules.iter().any(|rule| rule == identifier) {
+            let suggestion = suggest(identifier, self.rules);
+            self.errors
+                .push(Error::UndefinedRule(identifier.to_owned(), suggestion).spanning(span));
+        }
     }
-
-    Ok(())
 }
 
-fn check_nonterminals(
-    expression: &Spanned<Expression>,
-    rules: &[String],
-) -> Result<(), Spanned<Error>> {
-    match &expression.node {
-        Expression::Alternative {
-            first: box first,
-            second: box second,
-            rest,
-        } => {
-            check_nonterminals(first, rules)?;
-            check_nonterminals(second, rules)?;
-            for expression in rest.iter() {
-                check_nonterminals(expression, rules)?;
-            }
-            Ok(())
-        }
-        Expression::Sequence {
-            first: box first,
-            second: box second,
-            rest,
-        } => {
-            check_nonterminals(first, rules)?;
-            check_nonterminals(second, rules)?;
-            for expression in rest.iter() {
-                check_nonterminals(expression, rules)?;
-            }
-            Ok(())
-        }
-        Expression::Optional(box inner) => {
-            check_nonterminals(inner, rules)
-        }
-        Expression::Repeated(box inner) => {
-            check_nonterminals(inner, rules)
-        }
-        Expression::Factor {
-            primary: box primary,
-            ..
-        } => {
-            check_nonterminals(primary, rules)
-        }
-        Expression::Exception {
-            subject: box subject,
-            restriction: box restriction,
-        } => {
-            check_nonterminals(subject, rules)?;
-            check_nonterminals(restriction, rules)?;
-            Ok(())
-        }
-        Expression::Nonterminal(identifier) => {
-            if !rules.contains(identifier) {
-                Err(Error::UndefinedRule(identifier.clone()).spanning(expression.span))
-            } else {
-                Ok(())
+/// The number of single-character insertions, deletions, substitutions and
+/// transpositions of adjacent characters needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
             }
+            distances[i][j] = distance;
         }
-        Expression::Terminal(_) => Ok(()),
-        Expression::Special(_) => Ok(()),
-        Expression::Empty => Ok(()),
     }
+    distances[a.len()][b.len()]
+}
+
+/// The defined rule closest to `identifier`, if any is close enough to
+/// likely be what was meant. Rules differing only in case are preferred,
+/// then the earliest defined of the closest ones.
+fn suggest(identifier: &str, rules: &[String]) -> Option<String> {
+    let lowercase = identifier.to_lowercase();
+    if let Some(rule) = rules.iter().find(|rule| rule.to_lowercase() == lowercase) {
+        return Some(rule.clone());
+    }
+    let max_distance = identifier.chars().count() / 3;
+    rules
+        .iter()
+        .map(|rule| (edit_distance(identifier, rule), rule))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, rule)| rule.clone())
 }
 
 fn get_rule_identifiers(rules: &[Spanned<Production>]) -> Vec<String> {
@@ -265,10 +140,16 @@ fn get_rule_identifiers(rules: &[Spanned<Production>]) -> Vec<String> {
         .collect()
 }
 
-fn validate_nonterminals(
+/// Reports each use of a rule that isn't defined.
+pub fn validate_nonterminals(
     Spanned { node: grammar, .. }: &Spanned<Grammar>,
-) -> Result<(), Spanned<Error>> {
+    errors: &mut Vec<Spanned<Error>>,
+) {
     let rules = get_rule_identifiers(&grammar.productions);
+    let mut undefined = UndefinedRules {
+        rules: &rules,
+        errors,
+    };
 
     for Spanned {
         node: Production {
@@ -277,22 +158,52 @@ fn validate_nonterminals(
         ..
     } in grammar.productions.iter()
     {
-        check_nonterminals(&expression, &rules)?;
+        undefined.visit_expression(expression);
     }
-
-    Ok(())
 }
 
-fn validate_repetitions(
-    Spanned { node: grammar, .. }: &Spanned<Grammar>,
-) -> Result<(), Spanned<Error>> {
+/// Reports a start rule that the grammar doesn't define.
+pub fn validate_start(
+    Spanned {
+        node: grammar,
+        span,
+    }: &Spanned<Grammar>,
+    start: Option<&str>,
+    errors: &mut Vec<Spanned<Error>>,
+) {
     let rules = get_rule_identifiers(&grammar.productions);
+    if let Some(start) = start.filter(|start| !rules.iter().any(|rule| rule == start)) {
+        let suggestion = suggest(start, &rules);
+        let span = Span {
+            from: span.from,
+            to: span.from,
+        };
+        errors.push(Error::UndefinedStart(start.to_owned(), suggestion).spanning(span));
+    }
+}
+
+/// Reports the special sequences an expression uses that are not in
+/// `specials`.
+struct UnknownSpecials<'r> {
+    specials: &'r SpecialRegistry,
+    errors: &'r mut Vec<Spanned<Error>>,
+}
 
-    for spanned_production in grammar.productions.iter() {
-        if rules.iter().filter(|&n| *n == spanned_production.node.lhs.node).count() != 1 {
-            return Err(Error::MultipleDefinitions(spanned_production.node.lhs.node.to_owned()).spanning(spanned_production.node.lhs.span))
+impl<'a, 'r> Visitor<'a> for UnknownSpecials<'r> {
+    fn visit_special(&mut self, content: &'a str, span: Span) {
+        if !self.specials.contains(content) {
+            self.errors.push(Error::UnknownSpecial(content.to_owned()).spanning(span));
         }
     }
+}
+
+/// Reports each special sequence that is neither built in nor registered.
+pub fn validate_specials(
+    Spanned { node: grammar, .. }: &Spanned<Grammar>,
+    specials: &SpecialRegistry,
+    errors: &mut Vec<Spanned<Error>>,
+) {
+    let mut unknown = UnknownSpecials { specials, errors };
 
     for Spanned {
         node: Production {
@@ -301,17 +212,345 @@ fn validate_repetitions(
         ..
     } in grammar.productions.iter()
     {
-        check_nonterminals(&expression, &rules)?;
+        unknown.visit_expression(expression);
     }
+}
+
+/// Reports the parts of an expression the parser couldn't make sense of.
+struct SyntaxErrors<'r> {
+    errors: &'r mut Vec<Spanned<Error>>,
+}
 
-    Ok(())
+impl<'a, 'r> Visitor<'a> for SyntaxErrors<'r> {
+    fn visit_error(&mut self, span: Span) {
+        self.errors.push(Error::SyntaxError.spanning(span));
+    }
+}
+
+fn has_syntax_error(expression: &Spanned<Expression>) -> bool {
+    let mut errors = Vec::new();
+    SyntaxErrors {
+        errors: &mut errors,
+    }
+    .visit_expression(expression);
+    !errors.is_empty()
+}
+
+/// Reports each production with a syntax error, which the parser recovers
+/// from, but which can't be compiled.
+pub fn validate_syntax(
+    Spanned { node: grammar, .. }: &Spanned<Grammar>,
+    errors: &mut Vec<Spanned<Error>>,
+) {
+    let mut syntax_errors = SyntaxErrors { errors };
+    for Spanned {
+        node: Production { rhs, .. },
+        ..
+    } in grammar.productions.iter()
+    {
+        syntax_errors.visit_expression(rhs);
+    }
 }
 
-pub(super) fn preprocess(
+/// Reports each definition of a rule after its first one.
+pub fn validate_repetitions(
+    Spanned { node: grammar, .. }: &Spanned<Grammar>,
+    errors: &mut Vec<Spanned<Error>>,
+) {
+    let mut first_definitions: HashMap<&str, Span> = HashMap::new();
+
+    for Spanned {
+        node: Production { lhs, .. },
+        ..
+    } in grammar.productions.iter()
+    {
+        match first_definitions.get(lhs.node.as_str()) {
+            Some(first) => {
+                errors.push(Error::MultipleDefinitions(lhs.node.clone(), *first).spanning(lhs.span))
+            }
+            None => {
+                first_definitions.insert(&lhs.node, lhs.span);
+            }
+        }
+    }
+}
+
+/// The rules an expression uses.
+#[derive(Default)]
+struct Nonterminals<'a>(Vec<&'a str>);
+
+impl<'a> Visitor<'a> for Nonterminals<'a> {
+    fn visit_nonterminal(&mut self, identifier: &'a str, _: Span) {
+        self.0.push(identifier);
+    }
+}
+
+/// Warns about rules that no other rule uses, and about rules that are used,
+/// but only by rules that can't be reached from the start rule.
+pub fn find_unused_rules(
+    Spanned { node: grammar, .. }: &Spanned<Grammar>,
+    start: Option<&str>,
+    warnings: &mut Vec<Spanned<Warning>>,
+) {
+    let start = match start {
+        Some(start) => start,
+        None => match grammar.productions.first() {
+            Some(production) => production.node.lhs.node.as_str(),
+            None => return,
+        },
+    };
+    let mut references: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut used: HashSet<&str> = HashSet::new();
+    for Spanned { node: rule, .. } in grammar.productions.iter() {
+        let mut nonterminals = Nonterminals::default();
+        nonterminals.visit_expression(&rule.rhs);
+        let Nonterminals(nonterminals) = nonterminals;
+        used.extend(nonterminals.iter().filter(|identifier| **identifier != rule.lhs.node));
+        references.insert(&rule.lhs.node, nonterminals);
+    }
+    if !references.contains_key(start) {
+        return;
+    }
+    let mut reachable = HashSet::new();
+    let mut pending = vec![start];
+    while let Some(rule) = pending.pop() {
+        if reachable.insert(rule) {
+            pending.extend(references.get(rule).into_iter().flatten());
+        }
+    }
+
+    for Spanned {
+        node: Production { lhs, .. },
+        ..
+    } in grammar.productions.iter()
+    {
+        if lhs.node == start || reachable.contains(lhs.node.as_str()) {
+            continue;
+        }
+        let warning = if used.contains(lhs.node.as_str()) {
+            Warning::UnreachableRule(lhs.node.clone(), start.to_owned())
+        } else {
+            Warning::UnusedRule(lhs.node.clone())
+        };
+        warnings.push(warning.spanning(lhs.span));
+    }
+}
+
+/// Whether an expression may match empty input, or any input at all when
+/// `empty` is unset, given the rules that may. As in `base`, an exception is
+/// taken to match empty input when its subject may.
+struct Derives<'r> {
+    rules: &'r HashSet<&'r str>,
+    specials: &'r SpecialRegistry,
+    empty: bool,
+    result: bool,
+}
+
+impl<'r> Derives<'r> {
+    fn check(&mut self, expression: &Spanned<Expression>) -> bool {
+        self.visit_expression(expression);
+        self.result
+    }
+}
+
+impl<'a, 'r> Visitor<'a> for Derives<'r> {
+    fn visit_alternative(
+        &mut self,
+        first: &'a Spanned<Expression>,
+        second: &'a Spanned<Expression>,
+        rest: &'a [Spanned<Expression>],
+        _: Span,
+    ) {
+        self.result = [first, second]
+            .iter()
+            .copied()
+            .chain(rest.iter())
+            .any(|expression| self.check(expression));
+    }
+
+    fn visit_sequence(
+        &mut self,
+        first: &'a Spanned<Expression>,
+        second: &'a Spanned<Expression>,
+        rest: &'a [Spanned<Expression>],
+        _: Span,
+    ) {
+        self.result = [first, second]
+            .iter()
+            .copied()
+            .chain(rest.iter())
+            .all(|expression| self.check(expression));
+    }
+
+    fn visit_optional(&mut self, _: &'a Spanned<Expression>, _: Span) {
+        self.result = true;
+    }
+
+    fn visit_repeated(&mut self, _: &'a Spanned<Expression>, _: Span) {
+        self.result = true;
+    }
+
+    fn visit_factor(
+        &mut self,
+        count: &'a Spanned<usize>,
+        primary: &'a Spanned<Expression>,
+        _: Span,
+    ) {
+        self.result = count.node == 0 || self.check(primary);
+    }
+
+    fn visit_exception(
+        &mut self,
+        subject: &'a Spanned<Expression>,
+        _: &'a Spanned<Expression>,
+        _: Span,
+    ) {
+        self.result = self.check(subject);
+    }
+
+    fn visit_nonterminal(&mut self, identifier: &'a str, _: Span) {
+        self.result = self.rules.contains(identifier);
+    }
+
+    fn visit_terminal(&mut self, _: &'a str, _: Span) {
+        self.result = !self.empty;
+    }
+
+    fn visit_special(&mut self, content: &'a str, _: Span) {
+        self.result = !self.empty || self.specials.is_nullable(content);
+    }
+
+    fn visit_empty(&mut self, _: Span) {
+        self.result = true;
+    }
+
+    fn visit_error(&mut self, _: Span) {
+        self.result = false;
+    }
+}
+
+fn derives(
+    expression: &Spanned<Expression>,
+    rules: &HashSet<&str>,
+    specials: &SpecialRegistry,
+    empty: bool,
+) -> bool {
+    Derives {
+        rules,
+        specials,
+        empty,
+        result: false,
+    }
+    .check(expression)
+}
+
+/// The rules that may match empty input, or any input at all when `empty` is
+/// unset.
+fn deriving_rules<'g>(
+    grammar: &'g Grammar,
+    specials: &SpecialRegistry,
+    empty: bool,
+) -> HashSet<&'g str> {
+    let mut rules = HashSet::new();
+    loop {
+        let found = grammar
+            .productions
+            .iter()
+            .filter(|Spanned { node: rule, .. }| !rules.contains(rule.lhs.node.as_str()))
+            .filter(|Spanned { node: rule, .. }| derives(&rule.rhs, &rules, specials, empty))
+            .map(|Spanned { node: rule, .. }| rule.lhs.node.as_str())
+            .collect::<Vec<_>>();
+        if found.is_empty() {
+            return rules;
+        }
+        rules.extend(found);
+    }
+}
+
+/// Warns about rules that can't derive any finite string of terminals,
+/// because every one of their derivations uses them again.
+pub fn find_unproductive_rules(
+    Spanned { node: grammar, .. }: &Spanned<Grammar>,
+    warnings: &mut Vec<Spanned<Warning>>,
+) {
+    // whether special sequences match empty input doesn't matter here
+    let productive = deriving_rules(grammar, &SpecialRegistry::new(), false);
+
+    for Spanned {
+        node: Production { lhs, .. },
+        ..
+    } in grammar.productions.iter()
+    {
+        if !productive.contains(lhs.node.as_str()) {
+            warnings.push(Warning::UnproductiveRule(lhs.node.clone()).spanning(lhs.span));
+        }
+    }
+}
+
+/// Warns about the repetitions in an expression of expressions that may
+/// match empty input.
+struct NullableRepetitions<'r> {
+    nullable: &'r HashSet<&'r str>,
+    specials: &'r SpecialRegistry,
+    warnings: &'r mut Vec<Spanned<Warning>>,
+}
+
+impl<'a, 'r> Visitor<'a> for NullableRepetitions<'r> {
+    fn visit_repeated(&mut self, inner: &'a Spanned<Expression>, _: Span) {
+        if derives(inner, self.nullable, self.specials, true) {
+            self.warnings.push(Warning::NullableRepetition.spanning(inner.span));
+        }
+        self.visit_expression(inner);
+    }
+}
+
+/// Warns about repetitions of expressions that may match empty input, which
+/// would otherwise repeat forever.
+pub fn find_nullable_repetitions(
+    Spanned { node: grammar, .. }: &Spanned<Grammar>,
+    specials: &SpecialRegistry,
+    warnings: &mut Vec<Spanned<Warning>>,
+) {
+    let nullable = deriving_rules(grammar, specials, true);
+
+    let mut repetitions = NullableRepetitions {
+        nullable: &nullable,
+        specials,
+        warnings,
+    };
+    for Spanned { node: rule, .. } in grammar.productions.iter() {
+        repetitions.visit_expression(&rule.rhs);
+    }
+}
+
+/// Finds the parts of a valid grammar that are likely mistakes, but don't
+/// keep it from being checked.
+pub fn warn(spanned_grammar: &Spanned<Grammar>, options: &Options) -> Vec<Spanned<Warning>> {
+    let mut warnings = Vec::new();
+    find_unused_rules(spanned_grammar, options.start.as_deref(), &mut warnings);
+    find_unproductive_rules(spanned_grammar, &mut warnings);
+    find_nullable_repetitions(spanned_grammar, &options.specials, &mut warnings);
+    warnings
+}
+
+/// Runs every validation pass, and returns the grammar if none of them
+/// found an error.
+pub fn preprocess(
     spanned_grammar: Spanned<Grammar>,
-) -> Result<Spanned<Grammar>, Spanned<Error>> {
-    validate_repetitions(&spanned_grammar)?;
-    validate_nonterminals(&spanned_grammar)?;
-    validate_left_recursion(&spanned_grammar)?;
-    Ok(spanned_grammar)
+    options: &Options,
+) -> Result<Spanned<Grammar>, Vec<Spanned<Error>>> {
+    let mut errors = Vec::new();
+    validate_syntax(&spanned_grammar, &mut errors);
+    validate_repetitions(&spanned_grammar, &mut errors);
+    validate_nonterminals(&spanned_grammar, &mut errors);
+    validate_specials(&spanned_grammar, &options.specials, &mut errors);
+    validate_start(&spanned_grammar, options.start.as_deref(), &mut errors);
+    if !options.allow_left_recursion {
+        validate_left_recursion(&spanned_grammar, &options.specials, &mut errors);
+    }
+    if errors.is_empty() {
+        Ok(spanned_grammar)
+    } else {
+        Err(errors)
+    }
 }
@@ -28,3 +28,194 @@
 //         )
 //     );
 // }
+
+use super::{preprocess, warn, Error, Options, Span, Spanned, Spanning, Warning};
+use crate::span::Location;
+use crate::{lexer, parser};
+
+/// The span between two columns of the first line of an ASCII grammar.
+fn span(from: usize, to: usize) -> Span {
+    let location = |column| Location {
+        column,
+        line: 0,
+        offset: column,
+        utf16_column: column,
+    };
+    Span {
+        from: location(from),
+        to: location(to),
+    }
+}
+
+fn preprocess_str(input: &str) -> Result<(), Vec<Spanned<Error>>> {
+    preprocess_str_with(input, &Options::default())
+}
+
+fn preprocess_str_with(input: &str, options: &Options) -> Result<(), Vec<Spanned<Error>>> {
+    let (tokens, _) = lexer::lex(input);
+    let (grammar, _) = parser::parse(&tokens);
+    preprocess(grammar, options).map(|_| ())
+}
+
+fn warn_str(input: &str, start: Option<&str>) -> Vec<Spanned<Warning>> {
+    let (tokens, _) = lexer::lex(input);
+    let (grammar, _) = parser::parse(&tokens);
+    let options = Options {
+        start: start.map(str::to_owned),
+        ..Options::default()
+    };
+    warn(&grammar, &options)
+}
+
+#[test]
+fn test_special_sequences() {
+    assert_eq!(
+        preprocess_str("a = ? any character ?, ?Letter?, ? Lu ?, ? end  of input ?;"),
+        Ok(())
+    );
+    assert_eq!(
+        preprocess_str("a = 'x' | ? unicorn ?;"),
+        Err(vec![Error::UnknownSpecial(" unicorn ".to_owned()).spanning(span(10, 21))])
+    );
+    let mut options = Options::default();
+    options.specials.insert("unicorn", |_| None);
+    assert_eq!(preprocess_str_with("a = 'x' | ? Unicorn ?;", &options), Ok(()));
+}
+
+#[test]
+fn test_nullable_special_sequences() {
+    let left_recursion = || Error::LeftRecursion(vec!["a".to_owned(), "a".to_owned()]);
+    assert_eq!(
+        preprocess_str("a = ? end of input ?, a | 'x';"),
+        Err(vec![left_recursion().spanning(span(22, 23))])
+    );
+    let spaces = |input: &str| Some((input.len() - input.trim_start().len(), None));
+    let mut options = Options::default();
+    options.specials.insert("spaces", spaces);
+    assert_eq!(
+        preprocess_str_with("a = ? spaces ?, a | 'x';", &options),
+        Err(vec![left_recursion().spanning(span(16, 17))])
+    );
+    options.specials.insert_consuming("spaces", spaces);
+    assert_eq!(preprocess_str_with("a = ? spaces ?, a | 'x';", &options), Ok(()));
+}
+
+#[test]
+fn test_nullable_exceptions() {
+    let left_recursion = |chain: &[&str]| {
+        Error::LeftRecursion(chain.iter().map(|rule| (*rule).to_owned()).collect())
+    };
+    assert_eq!(
+        preprocess_str("a = [ 'x' ] - 'y', a | 'z';"),
+        Err(vec![left_recursion(&["a", "a"]).spanning(span(19, 20))])
+    );
+    assert_eq!(
+        preprocess_str("a = 'x' - a | 'z';"),
+        Err(vec![left_recursion(&["a", "a"]).spanning(span(10, 11))])
+    );
+}
+
+#[test]
+fn test_syntax_errors() {
+    assert_eq!(
+        preprocess_str("a = 'x' 'y'; b = a;"),
+        Err(vec![Error::SyntaxError.spanning(span(4, 12))])
+    );
+}
+
+#[test]
+fn test_every_violation() {
+    assert_eq!(
+        preprocess_str("a = b | ?x?; c = a | d; a = c, 'y';"),
+        Err(vec![
+            Error::MultipleDefinitions("a".to_owned(), span(0, 1)).spanning(span(24, 25)),
+            Error::UndefinedRule("b".to_owned(), None).spanning(span(4, 5)),
+            Error::UndefinedRule("d".to_owned(), None).spanning(span(21, 22)),
+            Error::UnknownSpecial("x".to_owned()).spanning(span(8, 11)),
+            Error::LeftRecursion(vec!["c".to_owned(), "a".to_owned(), "c".to_owned()])
+                .spanning(span(28, 29)),
+            Error::LeftRecursion(vec!["a".to_owned(), "c".to_owned(), "a".to_owned()])
+                .spanning(span(17, 18)),
+        ])
+    );
+}
+
+#[test]
+fn test_suggestions() {
+    assert_eq!(
+        preprocess_str("digit = '0'; number = digt, { Digit }, { digits }, { dgt };"),
+        Err(vec![
+            Error::UndefinedRule("digt".to_owned(), Some("digit".to_owned()))
+                .spanning(span(22, 26)),
+            Error::UndefinedRule("Digit".to_owned(), Some("digit".to_owned()))
+                .spanning(span(30, 35)),
+            Error::UndefinedRule("digits".to_owned(), Some("digit".to_owned()))
+                .spanning(span(41, 47)),
+            Error::UndefinedRule("dgt".to_owned(), None).spanning(span(53, 56)),
+        ])
+    );
+}
+
+#[test]
+fn test_undefined_start() {
+    let options = |start: &str| Options {
+        start: Some(start.to_owned()),
+        ..Options::default()
+    };
+    assert_eq!(preprocess_str_with("a = 'x';", &options("a")), Ok(()));
+    assert_eq!(
+        preprocess_str_with("a = 'x';", &options("nope")),
+        Err(vec![Error::UndefinedStart("nope".to_owned(), None).spanning(span(0, 0))])
+    );
+    assert_eq!(
+        preprocess_str_with("digit = '0';", &options("Digit")),
+        Err(vec![
+            Error::UndefinedStart("Digit".to_owned(), Some("digit".to_owned()))
+                .spanning(span(0, 0))
+        ])
+    );
+}
+
+#[test]
+fn test_unused_rules() {
+    let input = "s = a; a = 'x' | b; b = a; c = 'z' | c, d; d = 'y';";
+    assert_eq!(
+        warn_str(input, None),
+        vec![
+            Warning::UnusedRule("c".to_owned()).spanning(span(27, 28)),
+            Warning::UnreachableRule("d".to_owned(), "s".to_owned()).spanning(span(43, 44)),
+        ]
+    );
+    assert_eq!(
+        warn_str(input, Some("c")),
+        vec![
+            Warning::UnusedRule("s".to_owned()).spanning(span(0, 1)),
+            Warning::UnreachableRule("a".to_owned(), "c".to_owned()).spanning(span(7, 8)),
+            Warning::UnreachableRule("b".to_owned(), "c".to_owned()).spanning(span(20, 21)),
+        ]
+    );
+}
+
+#[test]
+fn test_unproductive_rules() {
+    assert_eq!(
+        warn_str("s = a | b; a = 'x', a; b = { a }, c; c = 2 * c | 0 * a;", None),
+        vec![Warning::UnproductiveRule("a".to_owned()).spanning(span(11, 12))]
+    );
+    assert_eq!(
+        warn_str("s = t, s | 'x'; t = s - 'y';", None),
+        vec![]
+    );
+}
+
+#[test]
+fn test_nullable_repetitions() {
+    assert_eq!(
+        warn_str("s = { [ 'a' ] }, { e | 'b' }, { 'c', { e } }; e = ;", None),
+        vec![
+            Warning::NullableRepetition.spanning(span(6, 13)),
+            Warning::NullableRepetition.spanning(span(19, 26)),
+            Warning::NullableRepetition.spanning(span(39, 40)),
+        ]
+    );
+}
@@ -0,0 +1,33 @@
+use super::{Span, Spanned, Spanning};
+use crate::impl_spanning;
+use std::fmt;
+
+#[derive(Debug, PartialEq)]
+pub enum Warning {
+    /// A rule used only by rules that the start rule never reaches, along
+    /// with the start rule.
+    UnreachableRule(String, String),
+    UnusedRule(String),
+    UnproductiveRule(String),
+    /// An expression inside a repetition that may match empty input.
+    NullableRepetition,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UnreachableRule(rule, start) => {
+                write!(f, "rule {} is unreachable from rule {}", rule, start)
+            }
+            Warning::UnusedRule(rule) => write!(f, "rule {} is never used", rule),
+            Warning::UnproductiveRule(rule) => {
+                write!(f, "rule {} never derives a finite string", rule)
+            }
+            Warning::NullableRepetition => {
+                write!(f, "repeated expression may match empty input")
+            }
+        }
+    }
+}
+
+impl_spanning!(Warning);
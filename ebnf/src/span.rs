@@ -2,13 +2,25 @@ use std::fmt;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Location {
+    /// Zero-based column in characters.
     pub column: usize,
+    /// Zero-based line number.
     pub line: usize,
+    /// Offset from the start of the input in bytes.
+    pub offset: usize,
+    /// Zero-based column in UTF-16 code units, which is how JavaScript
+    /// editors index a line.
+    pub utf16_column: usize,
 }
 
 impl Location {
     pub fn new() -> Location {
-        Location { column: 0, line: 0 }
+        Location {
+            column: 0,
+            line: 0,
+            offset: 0,
+            utf16_column: 0,
+        }
     }
 }
 
@@ -59,10 +71,12 @@ impl From<((usize, usize), (usize, usize))> for Span {
             from: Location {
                 column: (tuples.0).0,
                 line: (tuples.0).1,
+                ..Location::new()
             },
             to: Location {
                 column: (tuples.1).0,
                 line: (tuples.1).1,
+                ..Location::new()
             },
         }
     }
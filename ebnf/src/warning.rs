@@ -0,0 +1,88 @@
+use super::diagnostic::{Diagnostic, Level};
+use super::preprocessor;
+use super::span::{Span, Spanned, Spanning};
+use crate::impl_spanning;
+use std::fmt;
+
+/// A likely mistake in a grammar, which doesn't keep it from being checked.
+#[derive(Debug)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum WarningKind {
+    Preprocessor(preprocessor::warning::Warning),
+}
+
+impl_spanning!(Warning);
+
+impl From<Spanned<preprocessor::warning::Warning>> for Warning {
+    fn from(warning: Spanned<preprocessor::warning::Warning>) -> Warning {
+        Warning {
+            kind: WarningKind::Preprocessor(warning.node),
+            span: warning.span,
+        }
+    }
+}
+
+impl Warning {
+    pub fn notes(&self) -> Vec<String> {
+        use preprocessor::warning::Warning as Preprocessor;
+
+        match &self.kind {
+            WarningKind::Preprocessor(Preprocessor::UnreachableRule(_, start)) => vec![format!(
+                "every rule using it is itself unreachable from rule {}",
+                start
+            )],
+            WarningKind::Preprocessor(Preprocessor::NullableRepetition) => vec![
+                "the repetition stops as soon as an iteration matches empty input".to_owned(),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        use preprocessor::warning::Warning as Preprocessor;
+
+        match &self.kind {
+            WarningKind::Preprocessor(Preprocessor::UnusedRule(rule)) => {
+                Some(format!("remove rule {}, or use it in another rule", rule))
+            }
+            WarningKind::Preprocessor(Preprocessor::UnproductiveRule(rule)) => Some(format!(
+                "add an alternative to rule {} that doesn't use it, as in `a = 'x', a | 'x';`",
+                rule
+            )),
+            WarningKind::Preprocessor(Preprocessor::NullableRepetition) => Some(
+                "make the expression match at least one character, as in `{ 'a' }` instead of \
+                 `{ [ 'a' ] }`"
+                    .to_owned(),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Renders the warning with the lines of `source` it points at, its
+    /// notes and help, optionally highlighted with ANSI escapes.
+    pub fn render(&self, source: &str, color: bool) -> String {
+        Diagnostic {
+            level: Level::Warning,
+            message: self.to_string(),
+            span: self.span,
+            labels: Vec::new(),
+            notes: self.notes(),
+            help: self.help(),
+            source,
+        }
+        .render(color)
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            WarningKind::Preprocessor(inner) => write!(f, "{}", inner),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use base::{Position, Span};
+
+#[test]
+fn test_rule_order() {
+    let (grammar, _) = ebnf::parse("z = y; y = x; x = 'x'; w = z;").unwrap();
+    assert_eq!(grammar.keys().collect::<Vec<_>>(), vec!["z", "y", "x", "w"]);
+    assert_eq!(grammar.start(), Some("z"));
+
+    let options = ebnf::Options {
+        start: Some("w".to_owned()),
+        ..ebnf::Options::default()
+    };
+    let (grammar, _) = ebnf::parse_with("z = y; y = x; x = 'x'; w = z;", &options).unwrap();
+    assert_eq!(grammar.start(), Some("w"));
+}
+
+#[test]
+fn test_rule_metadata() {
+    let input = "(* the whole grammar *)\n\n\
+                 (* first *)\n(* and only *)\na = b; (* about a *)\n\
+                 (* ż *) b = 'ą';";
+    let (grammar, _) = ebnf::parse(input).unwrap();
+    let a = grammar.rule("a").unwrap();
+    assert_eq!(a.doc.as_deref(), Some("first\nand only"));
+    let b = grammar.rule("b").unwrap();
+    assert_eq!(b.doc.as_deref(), Some("ż"));
+    assert_eq!(
+        b.span,
+        Some(Span {
+            from: Position {
+                offset: 82,
+                char: 81,
+                line: 5,
+                column: 8,
+            },
+            to: Position {
+                offset: 91,
+                char: 89,
+                line: 5,
+                column: 16,
+            },
+        })
+    );
+    assert_eq!(&input[82..91], "b = 'ą';");
+}
+
+#[test]
+fn test_precompiled() {
+    let input = "(* a signed number *)\nnumber = [ '-' ], digit, { digit };\ndigit = ?digit?;";
+    let (grammar, _) = ebnf::parse(input).unwrap();
+    let decoded = base::binary::decode(&base::binary::encode(&grammar)).unwrap();
+    assert_eq!(decoded.rules(), grammar.rules());
+    assert!(base::check("-12", &decoded, decoded.start().unwrap()).is_ok());
+}
+
+#[test]
+fn test_left_recursion_agrees_with_builder() {
+    let inputs = [
+        "a = 'x', a | 'y';",
+        "a = 0 * a, 'x';",
+        "a = b, a | 'x'; b = ;",
+        "a = { 'x' }, b; b = a | 'y';",
+        "a = [ 'x' ] - 'y', a | 'z';",
+        "a = 'x' - a | 'z';",
+    ];
+    let options = ebnf::Options {
+        allow_left_recursion: true,
+        ..ebnf::Options::default()
+    };
+    for input in inputs.iter() {
+        let (grammar, _) = ebnf::parse_with(input, &options).unwrap();
+        let builder = grammar
+            .iter()
+            .fold(base::Builder::new(), |builder, (name, expression)| {
+                builder.rule(name, expression.clone())
+            });
+        assert_eq!(ebnf::parse(input).is_ok(), builder.build().is_ok(), "{}", input);
+    }
+}
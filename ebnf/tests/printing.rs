@@ -0,0 +1,49 @@
+use base::{to_ebnf, Expression, Grammar};
+
+fn round_trip(grammar: &Grammar) -> Grammar {
+    let printed = to_ebnf(grammar);
+    match ebnf::parse(&printed) {
+        Ok((parsed, _)) => parsed,
+        Err(errors) => panic!("{:?} in\n{}", errors, printed),
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let inputs = [
+        "a = 'x', b | c; b = [ 'y' ] | { 'z' }; c = ?letter?;",
+        "a = b - 'x', 'y'; b = ('x', b | 'y') - ('x' | 'z');",
+        "a = 2 * ('x' | 'y'), 3 * 'z', 'w' - 2 * 'w';",
+        "a = \"'\", '\"' | 'x' | ; b = [ ], { a | 'y' };",
+        "a = - 'x' | 'y', ('z', 'w' | 'v');",
+    ];
+    for input in inputs.iter() {
+        let (grammar, _) = ebnf::parse(input).unwrap();
+        assert_eq!(round_trip(&grammar), grammar, "{}", input);
+    }
+}
+
+#[test]
+fn test_built_grammar() {
+    let mut grammar = Grammar::new();
+    grammar.insert(
+        "a".to_owned(),
+        Expression::Factor {
+            count: 2,
+            primary: Box::new(Expression::Terminal("it's \"quoted\"".to_owned())),
+        },
+    );
+    let parsed = round_trip(&grammar);
+    assert_eq!(to_ebnf(&parsed), "a = 2 * (\"it's \", '\"quoted\"');\n");
+}
+
+#[test]
+fn test_eliminated_grammar() {
+    let options = ebnf::Options {
+        allow_left_recursion: true,
+        ..ebnf::Options::default()
+    };
+    let (grammar, _) = ebnf::parse_with("e = e, '+', t | t; t = 'n';", &options).unwrap();
+    let eliminated = base::eliminate_left_recursion(&grammar).unwrap();
+    assert_eq!(round_trip(&eliminated.grammar), eliminated.grammar);
+}
@@ -0,0 +1,46 @@
+use ebnf::lexer::{self, Token};
+use ebnf::parser::{self, Expression};
+use ebnf::preprocessor::{self, error::Error, warning::Warning};
+use ebnf::{compiler, Options};
+
+#[test]
+fn test_stages() {
+    let input = "a = 'x', b; c = a;";
+    let (tokens, errors) = lexer::lex(input);
+    assert!(errors.is_empty());
+    assert_eq!(tokens[0].node, Token::Nonterminal("a".to_owned()));
+
+    let (grammar, errors) = parser::parse(&tokens);
+    assert!(errors.is_empty());
+    assert_eq!(
+        grammar.node.productions[1].node.rhs.node,
+        Expression::Nonterminal("a".to_owned())
+    );
+
+    let mut errors = Vec::new();
+    preprocessor::validate_nonterminals(&grammar, &mut errors);
+    assert_eq!(
+        errors
+            .into_iter()
+            .map(|error| error.node)
+            .collect::<Vec<_>>(),
+        vec![Error::UndefinedRule("b".to_owned(), None)]
+    );
+
+    let mut warnings = Vec::new();
+    preprocessor::find_unused_rules(&grammar, Some("c"), &mut warnings);
+    assert!(warnings.is_empty());
+    preprocessor::find_unused_rules(&grammar, None, &mut warnings);
+    assert_eq!(warnings[0].node, Warning::UnusedRule("c".to_owned()));
+
+    let input = "a = 'x';";
+    let (tokens, _) = lexer::lex(input);
+    let (grammar, _) = parser::parse(&tokens);
+    let grammar = preprocessor::preprocess(grammar, &Options::default()).unwrap();
+    let compiled = compiler::compile(grammar.clone());
+    assert_eq!(compiled["a"], base::Expression::Terminal("x".to_owned()));
+    assert_eq!(compiled.rule("a").unwrap().span, None);
+    let with_source = compiler::compile_with_source(grammar, input);
+    assert_eq!(with_source, compiled);
+    assert!(with_source.rule("a").unwrap().span.is_some());
+}